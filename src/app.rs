@@ -0,0 +1,335 @@
+use raylib::prelude::*;
+
+use crate::capture::FrameCapture;
+use crate::game::{load_maze_with_sprites, parse_level, read_level_source, GameState, LevelStats};
+use crate::generator::{generate_level, GeneratorConfig};
+use crate::platform::Platform;
+use crate::platform::raylib_backend::RaylibPlatform;
+use crate::play::{load_level_textures, load_sounds, load_sprite_textures, play_level};
+use crate::replay::{InputTape, Recording};
+use crate::texture::TextureManager;
+use crate::LEVEL_FILES;
+
+const MENU_MUSIC: &str = "./audio/music/menu.ogg";
+
+/// Juego con ventana: menú de niveles, partida y pantallas de resultados.
+/// Con `record_path` se guarda la entrada del último nivel jugado; las
+/// secuencias de frames guardan uno de cada `capture_every`.
+pub fn run(block_size: usize, record_path: Option<String>, capture_every: u32) {
+    let window_width = 930;
+    let window_height = 630;
+    let mut platform = RaylibPlatform::new(window_width, window_height, "Raycaster Game");
+
+    let mut texture_manager = TextureManager::new();
+    let mut capture = FrameCapture::new(capture_every);
+
+    texture_manager.generate_default_textures();
+    load_sprite_textures(&mut texture_manager);
+    load_sounds(&mut platform);
+
+    // Cargar y mantener la textura de fondo del menú como Texture2D de Raylib
+    let menu_bg_texture = match Image::load_image("./textures/menu_bg.jpg") {
+        Ok(img) => Some(platform.rl.load_texture_from_image(&platform.thread, &img).unwrap()),
+        Err(_) => {
+            println!("No se pudo cargar la imagen de fondo del menú, usando color sólido");
+            None
+        }
+    };
+
+    let mut game_state = GameState::Menu;
+    let level_files = LEVEL_FILES;
+    // Los nombres del menú salen de la cabecera de cada nivel
+    let mut level_names: Vec<String> = level_files
+        .iter()
+        .map(|file| match load_maze_with_sprites(file, block_size) {
+            Ok(level) => level.name,
+            Err(e) => {
+                println!("Error en el nivel '{}': {}", file, e);
+                file.to_string()
+            }
+        })
+        .collect();
+    // Última opción del menú: niveles generados sin fin
+    let endless_option = level_names.len();
+    level_names.push("Modo infinito".to_string());
+    let mut endless_depth = 0;
+    let mut endless_seed: u64 = 0;
+
+    let mut selected_level = 0;
+    let mut current_level_name = String::new();
+    let mut level_error: Option<String> = None;
+    let mut level_stats = LevelStats::default();
+    
+    platform.play_music(MENU_MUSIC);
+    while !platform.should_close() && game_state != GameState::Exiting {
+        match game_state {
+            GameState::Menu => {
+                
+                let mut d = platform.rl.begin_drawing(&platform.thread);
+                
+                // Renderizar fondo
+                if let Some(bg_texture) = &menu_bg_texture {
+                    // Calcular escalado para que la imagen cubra toda la pantalla
+                    let scale_x = window_width as f32 / bg_texture.width as f32;
+                    let scale_y = window_height as f32 / bg_texture.height as f32;
+                    let scale = scale_x.max(scale_y); // Usar el mayor para cubrir toda la pantalla
+                    
+                    // Centrar la imagen
+                    let scaled_width = bg_texture.width as f32 * scale;
+                    let scaled_height = bg_texture.height as f32 * scale;
+                    let offset_x = (window_width as f32 - scaled_width) / 2.0;
+                    let offset_y = (window_height as f32 - scaled_height) / 2.0;
+                    
+                    d.draw_texture_ex(
+                        bg_texture,
+                        Vector2::new(offset_x, offset_y),
+                        0.0,
+                        scale,
+                        Color::WHITE,
+                    );
+                } else {
+                    // Fondo alternativo si no se puede cargar la imagen
+                    d.clear_background(Color::new(20, 20, 40, 255));
+                }
+                
+                // Overlay semi-transparente para mejorar legibilidad
+                d.draw_rectangle(0, 0, window_width, window_height, Color::new(0, 0, 0, 100));
+                
+                // Título con contorno para mejor visibilidad
+                let title_text = ">> Horror Game <<";
+                let title_x = window_width / 2 - 150;
+                let title_y = 100;
+                
+                // Contorno del título
+                for dx in -2..=2 {
+                    for dy in -2..=2 {
+                        if dx != 0 || dy != 0 {
+                            d.draw_text(title_text, title_x + dx, title_y + dy, 30, Color::BLACK);
+                        }
+                    }
+                }
+                d.draw_text(title_text, title_x, title_y, 30, Color::RAYWHITE);
+                
+                // Instrucciones con contorno
+                let instruction_text = "Selecciona un nivel:";
+                let inst_x = window_width / 2 - 150;
+                let inst_y = 180;
+                
+                // Contorno de instrucciones
+                d.draw_text(instruction_text, inst_x + 1, inst_y + 1, 22, Color::BLACK);
+                d.draw_text(instruction_text, inst_x, inst_y, 22, Color::LIGHTGRAY);
+
+                // Dibujar opciones de nivel con selección y contorno
+                for (i, name) in level_names.iter().enumerate() {
+                    let color = if i == selected_level { Color::YELLOW } else { Color::GRAY };
+                    let marker = if i == selected_level { ">" } else { " " };
+                    let level_text = format!("{} {}", marker, name);
+                    let level_x = window_width / 2 - 100;
+                    let level_y = 220 + i as i32 * 40;
+                    
+                    // Contorno para opciones de nivel
+                    d.draw_text(&level_text, level_x + 1, level_y + 1, 28, Color::BLACK);
+                    d.draw_text(&level_text, level_x, level_y, 28, color);
+                }
+
+                // Instrucciones de control con contorno
+                let control_texts = [
+                    "Usa W/S para cambiar nivel",
+                    "ENTER para jugar | ESC para salir"
+                ];
+                
+                for (i, text) in control_texts.iter().enumerate() {
+                    let ctrl_x = window_width / 2 - 150;
+                    let ctrl_y = 370 + i as i32 * 30;
+                    
+                    d.draw_text(text, ctrl_x + 1, ctrl_y + 1, 18, Color::BLACK);
+                    d.draw_text(text, ctrl_x, ctrl_y, 18, Color::DARKGRAY);
+                }
+
+                // Error del último nivel que no se pudo cargar
+                if let Some(error) = &level_error {
+                    d.draw_text(error, 20, window_height - 40, 16, Color::BLACK);
+                    d.draw_text(error, 19, window_height - 41, 16, Color::RED);
+                }
+
+                // Navegación con W/S
+                if d.is_key_pressed(KeyboardKey::KEY_W) {
+                    if selected_level > 0 {
+                        selected_level -= 1;
+                    }
+                }
+                if d.is_key_pressed(KeyboardKey::KEY_S) {
+                    if selected_level < level_names.len() - 1 {
+                        selected_level += 1;
+                    }
+                }
+
+                if d.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                    if selected_level == endless_option {
+                        endless_depth = 0;
+                        endless_seed = rand::random();
+                    }
+                    game_state = GameState::Playing;
+                } else if d.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                    game_state = GameState::Exiting;
+                }
+            }
+
+            GameState::Playing => {
+                // El texto del nivel se guarda para las grabaciones
+                let source = if selected_level == endless_option {
+                    Ok(generate_level(&GeneratorConfig::endless(endless_depth, endless_seed)))
+                } else {
+                    read_level_source(level_files[selected_level])
+                };
+                let loaded = source.and_then(|source| Ok((parse_level(&source, block_size)?, source)));
+                let (level, source) = match loaded {
+                    Ok(loaded) => loaded,
+                    Err(e) => {
+                        let error = format!("Error en '{}': {}", level_names[selected_level], e);
+                        println!("{}", error);
+                        level_error = Some(error);
+                        game_state = GameState::Menu;
+                        continue;
+                    }
+                };
+                level_error = None;
+                current_level_name = level.name.clone();
+
+                let mut tape = match record_path {
+                    Some(_) => InputTape::Recording(Recording::new(&source)),
+                    None => InputTape::Live,
+                };
+
+                load_level_textures(&mut texture_manager, &level);
+                game_state = play_level(&mut platform, level, &texture_manager, block_size, &mut level_stats, &mut tape, &mut capture);
+                if let (Some(path), Some(recording)) = (&record_path, tape.into_recording()) {
+                    match recording.save(path) {
+                        Ok(_) => println!("Partida grabada en '{}' ({} pasos)", path, recording.ticks.len()),
+                        Err(e) => println!("{}", e),
+                    }
+                }
+                if game_state != GameState::Exiting {
+                    platform.play_music(MENU_MUSIC);
+                    platform.show_cursor();
+                }
+            }
+
+            GameState::LevelComplete => {
+                let is_endless = selected_level == endless_option;
+                let is_last_level = !is_endless && selected_level + 1 >= level_files.len();
+                let mut d = platform.rl.begin_drawing(&platform.thread);
+                d.clear_background(Color::new(10, 10, 20, 255));
+
+                // Título con contorno
+                let title_text = "Nivel completado";
+                let title_x = window_width / 2 - 150;
+                let title_y = 100;
+                for dx in -2..=2 {
+                    for dy in -2..=2 {
+                        if dx != 0 || dy != 0 {
+                            d.draw_text(title_text, title_x + dx, title_y + dy, 34, Color::BLACK);
+                        }
+                    }
+                }
+                d.draw_text(title_text, title_x, title_y, 34, Color::GOLD);
+
+                // Resultados del nivel
+                let minutes = level_stats.elapsed as u32 / 60;
+                let seconds = level_stats.elapsed as u32 % 60;
+                let result_texts = [
+                    current_level_name.clone(),
+                    format!("Tiempo: {:02}:{:02}", minutes, seconds),
+                    format!("Objetos recogidos: {}", level_stats.pickups),
+                    format!("Puntos: {}", level_stats.score),
+                    format!("Daño recibido: {}", level_stats.damage_taken),
+                ];
+                for (i, text) in result_texts.iter().enumerate() {
+                    let text_x = window_width / 2 - 150;
+                    let text_y = 190 + i as i32 * 40;
+                    d.draw_text(text, text_x + 1, text_y + 1, 24, Color::BLACK);
+                    d.draw_text(text, text_x, text_y, 24, Color::LIGHTGRAY);
+                }
+
+                let continue_text = if is_last_level {
+                    "Juego completado! ENTER para volver al menu"
+                } else {
+                    "ENTER para continuar al siguiente nivel"
+                };
+                d.draw_text(continue_text, window_width / 2 - 150, 400, 18, Color::DARKGRAY);
+
+                if d.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                    if is_last_level {
+                        game_state = GameState::Menu;
+                    } else if is_endless {
+                        endless_depth += 1;
+                        endless_seed = rand::random();
+                        game_state = GameState::Playing;
+                    } else {
+                        selected_level += 1;
+                        game_state = GameState::Playing;
+                    }
+                }
+            }
+
+            GameState::GameOver => {
+                let mut d = platform.rl.begin_drawing(&platform.thread);
+                d.clear_background(Color::new(30, 0, 0, 255));
+
+                // Título con contorno
+                let title_text = "Has muerto";
+                let title_x = window_width / 2 - 100;
+                let title_y = 150;
+                for dx in -2..=2 {
+                    for dy in -2..=2 {
+                        if dx != 0 || dy != 0 {
+                            d.draw_text(title_text, title_x + dx, title_y + dy, 40, Color::BLACK);
+                        }
+                    }
+                }
+                d.draw_text(title_text, title_x, title_y, 40, Color::RED);
+
+                let control_texts = [
+                    "ENTER para reintentar el nivel",
+                    "P para volver al menu"
+                ];
+                for (i, text) in control_texts.iter().enumerate() {
+                    let ctrl_x = window_width / 2 - 150;
+                    let ctrl_y = 260 + i as i32 * 30;
+                    d.draw_text(text, ctrl_x + 1, ctrl_y + 1, 20, Color::BLACK);
+                    d.draw_text(text, ctrl_x, ctrl_y, 20, Color::LIGHTGRAY);
+                }
+
+                if d.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                    game_state = GameState::Playing;
+                } else if d.is_key_pressed(KeyboardKey::KEY_P) {
+                    game_state = GameState::Menu;
+                }
+            }
+
+            GameState::Exiting => break,
+        }
+    }
+}
+
+/// Ver una grabación en la ventana del juego
+pub fn watch_replay(recording: Recording, block_size: usize) {
+    let level = match parse_level(&recording.level_source, block_size) {
+        Ok(level) => level,
+        Err(e) => {
+            println!("nivel de la grabación: {}", e);
+            return;
+        }
+    };
+
+    let mut platform = RaylibPlatform::new(930, 630, "Raycaster Game - Repetición");
+    let mut texture_manager = TextureManager::new();
+    load_sprite_textures(&mut texture_manager);
+    load_sounds(&mut platform);
+    load_level_textures(&mut texture_manager, &level);
+
+    let mut stats = LevelStats::default();
+    let mut tape = InputTape::Replaying { recording, next: 0 };
+    let mut capture = FrameCapture::new(1);
+    play_level(&mut platform, level, &texture_manager, block_size, &mut stats, &mut tape, &mut capture);
+}
@@ -1,10 +1,11 @@
 use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
-use std::{fs::File, io::BufReader, sync::Arc};
+use std::{collections::HashMap, fs::File, io::{BufReader, Cursor, Read}, sync::Arc};
 
 pub struct AudioManager {
     _stream: Option<OutputStream>,        // Mantiene vivo el stream si hay audio
     handle: Option<OutputStreamHandle>,   // Controlador si hay audio
     music_sink: Option<Arc<Sink>>,
+    sounds: HashMap<String, (Arc<[u8]>, f32)>, // Efectos precargados (bytes, volumen)
 }
 
 impl AudioManager {
@@ -14,6 +15,7 @@ impl AudioManager {
             _stream: Some(stream),
             handle: Some(handle),
             music_sink: None,
+            sounds: HashMap::new(),
         },
         Err(_) => {
             println!("No se encontró dispositivo de audio, se desactiva sonido.");
@@ -21,6 +23,7 @@ impl AudioManager {
                 _stream: None,
                 handle: None,
                 music_sink: None,
+                sounds: HashMap::new(),
             }
         }
     }
@@ -58,6 +61,7 @@ impl AudioManager {
     }
 
     /// Reproduce efecto de sonido una vez
+    #[allow(dead_code)]
     pub fn play_sound(&self, path: &str) {
         if self.handle.is_none() {
             return;
@@ -77,18 +81,59 @@ impl AudioManager {
         sink.detach();
     }
 
+    /// Precargar un efecto de sonido en memoria para reproducirlo por nombre
+    pub fn load_sound(&mut self, name: &str, path: &str, volume: f32) {
+        if self.handle.is_none() {
+            return;
+        }
+
+        let mut bytes = Vec::new();
+        match File::open(path).and_then(|mut f| f.read_to_end(&mut bytes)) {
+            Ok(_) => {
+                self.sounds.insert(name.to_string(), (Arc::from(bytes), volume));
+            }
+            Err(_) => println!("No se encontró el archivo de audio: {}", path),
+        }
+    }
+
+    /// Reproduce una vez un efecto cargado con `load_sound`
+    pub fn play_effect(&self, name: &str) {
+        let (Some(handle), Some((bytes, volume))) = (&self.handle, self.sounds.get(name)) else {
+            return;
+        };
+
+        let source = match Decoder::new(Cursor::new(bytes.clone())) {
+            Ok(s) => s,
+            Err(_) => {
+                println!("Error decodificando el efecto: {}", name);
+                return;
+            }
+        };
+
+        let sink = match Sink::try_new(handle) {
+            Ok(s) => s,
+            Err(_) => return,
+        };
+        sink.set_volume(*volume);
+        sink.append(source);
+        sink.detach();
+    }
+
+    #[allow(dead_code)]
     pub fn pause_music(&self) {
         if let Some(sink) = &self.music_sink {
             sink.pause();
         }
     }
 
+    #[allow(dead_code)]
     pub fn resume_music(&self) {
         if let Some(sink) = &self.music_sink {
             sink.play();
         }
     }
 
+    #[allow(dead_code)]
     pub fn stop_music(&mut self) {
         if let Some(sink) = &self.music_sink {
             sink.stop();
@@ -0,0 +1,81 @@
+use std::time::Instant;
+
+use crate::framebuffer::Framebuffer;
+use crate::game::{load_maze_with_sprites, Level};
+use crate::lights::LightMap;
+use crate::math::Color;
+use crate::play::load_level_textures;
+use crate::player::Player;
+use crate::renderer::{render_threads, render_world_with_textures_threaded};
+use crate::texture::TextureManager;
+use crate::LEVEL_FILES;
+
+/// Resoluciones medidas: la de la ventana del juego y otras más grandes
+const RESOLUTIONS: [(u32, u32); 4] = [(930, 630), (1280, 720), (1920, 1080), (2560, 1440)];
+
+/// Milisegundos promedio por frame del render reducido con `threads` hilos.
+/// La cámara gira un poco en cada frame para no medir siempre la misma vista.
+fn measure(level: &Level, texture_manager: &TextureManager, width: u32, height: u32, threads: usize, frames: u32, block_size: usize) -> f64 {
+    let mut framebuffer = Framebuffer::new(width, height, Color::BLACK);
+    let mut player = Player::new(level.spawn);
+    player.a = level.facing;
+    let lights = LightMap::from_sprites(&level.maze, &level.sprites, block_size);
+
+    // Un frame sin medir para que las texturas ya estén en caché
+    render_world_with_textures_threaded(&mut framebuffer, &level.maze, &player, block_size, texture_manager, &lights, threads);
+
+    let start = Instant::now();
+    for frame in 0..frames {
+        player.a = level.facing + frame as f32 * 0.02;
+        render_world_with_textures_threaded(&mut framebuffer, &level.maze, &player, block_size, texture_manager, &lights, threads);
+    }
+    start.elapsed().as_secs_f64() * 1000.0 / frames as f64
+}
+
+/// Subcomando `benchmark [--frames N] [--threads N]`: compara el render
+/// reducido en un solo hilo contra el reparto en varios hilos
+pub fn run(args: &[String], block_size: usize) -> i32 {
+    let mut frames: u32 = 120;
+    let mut threads = render_threads();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let Some(value) = iter.next().and_then(|v| v.parse::<u32>().ok()).filter(|&v| v > 0) else {
+            println!("Se esperaba un número positivo después de '{}'", arg);
+            return 2;
+        };
+        match arg.as_str() {
+            "--frames" => frames = value,
+            "--threads" => threads = value as usize,
+            other => {
+                println!("Opción desconocida '{}'", other);
+                return 2;
+            }
+        }
+    }
+
+    let level = match load_maze_with_sprites(LEVEL_FILES[0], block_size) {
+        Ok(level) => level,
+        Err(e) => {
+            println!("{}: error: {}", LEVEL_FILES[0], e);
+            return 1;
+        }
+    };
+    let mut texture_manager = TextureManager::new();
+    load_level_textures(&mut texture_manager, &level);
+
+    println!("{} frames por medición, {} hilos ({} núcleos disponibles)", frames, threads, render_threads());
+    println!("{:>11} | {:>10} | {:>10} | {:>8}", "resolución", "1 hilo", format!("{} hilos", threads), "mejora");
+    for (width, height) in RESOLUTIONS {
+        let single = measure(&level, &texture_manager, width, height, 1, frames, block_size);
+        let threaded = measure(&level, &texture_manager, width, height, threads, frames, block_size);
+        println!(
+            "{:>11} | {:>7.2} ms | {:>7.2} ms | {:>7.2}x",
+            format!("{}x{}", width, height),
+            single,
+            threaded,
+            single / threaded
+        );
+    }
+    0
+}
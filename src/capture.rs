@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use image::RgbaImage;
+
+use crate::framebuffer::Framebuffer;
+use crate::snapshot::framebuffer_to_image;
+
+/// Carpeta donde se guardan capturas y secuencias
+const CAPTURE_DIR: &str = "./capturas";
+
+/// Capturas de pantalla y secuencias de frames del juego. Los PNG se
+/// codifican en un hilo aparte para no frenar el juego mientras se graba.
+pub struct FrameCapture {
+    pub every: u32,                 // en una secuencia, guardar uno de cada `every` frames
+    sequence: Option<Sequence>,
+    sender: Option<Sender<(RgbaImage, PathBuf)>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+/// Secuencia en curso: `frame_NNNNN.png` numerados dentro de `dir`
+struct Sequence {
+    dir: PathBuf,
+    frames_seen: u32,
+    saved: u32,
+}
+
+impl FrameCapture {
+    pub fn new(every: u32) -> Self {
+        let (sender, receiver) = channel::<(RgbaImage, PathBuf)>();
+        let writer = thread::spawn(move || {
+            for (image, path) in receiver {
+                if let Err(e) = image.save(&path) {
+                    println!("No se pudo guardar '{}': {}", path.display(), e);
+                }
+            }
+        });
+        FrameCapture {
+            every: every.max(1),
+            sequence: None,
+            sender: Some(sender),
+            writer: Some(writer),
+        }
+    }
+
+    /// Guardar el frame actual como `captura_<marca de tiempo>.png`.
+    /// Devuelve la ruta del archivo.
+    pub fn screenshot(&mut self, framebuffer: &Framebuffer) -> Result<PathBuf, String> {
+        fs::create_dir_all(CAPTURE_DIR).map_err(|e| format!("No se pudo crear '{}': {}", CAPTURE_DIR, e))?;
+        let path = Path::new(CAPTURE_DIR).join(format!("captura_{}.png", timestamp()));
+        self.write(framebuffer, path.clone());
+        Ok(path)
+    }
+
+    /// Empezar o terminar una secuencia. Devuelve un mensaje para el jugador.
+    pub fn toggle_sequence(&mut self) -> Result<String, String> {
+        if let Some(sequence) = self.sequence.take() {
+            return Ok(format!("Secuencia guardada: {} frames en {}", sequence.saved, sequence.dir.display()));
+        }
+        let dir = Path::new(CAPTURE_DIR).join(format!("secuencia_{}", timestamp()));
+        fs::create_dir_all(&dir).map_err(|e| format!("No se pudo crear '{}': {}", dir.display(), e))?;
+        let message = format!("Grabando secuencia en {}", dir.display());
+        self.sequence = Some(Sequence { dir, frames_seen: 0, saved: 0 });
+        Ok(message)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.sequence.is_some()
+    }
+
+    /// Frames guardados en la secuencia actual
+    pub fn saved_frames(&self) -> u32 {
+        self.sequence.as_ref().map_or(0, |sequence| sequence.saved)
+    }
+
+    /// Llamar una vez por frame dibujado; guarda uno de cada `every` si hay
+    /// una secuencia en curso
+    pub fn capture(&mut self, framebuffer: &Framebuffer) {
+        let every = self.every;
+        let Some(sequence) = self.sequence.as_mut() else {
+            return;
+        };
+        sequence.frames_seen += 1;
+        if !(sequence.frames_seen - 1).is_multiple_of(every) {
+            return;
+        }
+        sequence.saved += 1;
+        let path = sequence.dir.join(format!("frame_{:05}.png", sequence.saved));
+        self.write(framebuffer, path);
+    }
+
+    fn write(&self, framebuffer: &Framebuffer, path: PathBuf) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send((framebuffer_to_image(framebuffer), path));
+        }
+    }
+}
+
+impl Drop for FrameCapture {
+    /// Esperar a que se terminen de escribir los PNG pendientes
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Milisegundos desde la época Unix, para nombres de archivo únicos y ordenados
+fn timestamp() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis())
+}
@@ -1,59 +1,466 @@
-use raylib::prelude::*;
+use crate::math::{Segment, Vector2};
 use crate::framebuffer::{Framebuffer, rgba_to_u32};
-use crate::player::Player;
-use crate::game::Maze;
+use crate::game::{Maze, is_open_cell, is_thin_wall};
+use crate::door::Door;
 
+/// Cara de la celda que golpeó el rayo
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WallSide {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl WallSide {
+    /// Caras verticales (paralelas al eje Y del mapa)
+    pub fn is_vertical(&self) -> bool {
+        matches!(self, WallSide::East | WallSide::West)
+    }
+
+    /// Normal de la cara, apuntando hacia afuera de la celda
+    pub fn normal(&self) -> Vector2 {
+        match self {
+            WallSide::North => Vector2::new(0.0, -1.0),
+            WallSide::South => Vector2::new(0.0, 1.0),
+            WallSide::East => Vector2::new(1.0, 0.0),
+            WallSide::West => Vector2::new(-1.0, 0.0),
+        }
+    }
+}
+
+/// Resultado completo de lanzar un rayo contra el maze
 pub struct Intersect{
-    pub distance: f32,
-    pub impact: char
+    pub distance: f32,         // distancia euclidiana desde el origen del rayo
+    pub impact: char,          // ' ' si el rayo no golpeó nada
+    pub hit: Vector2,          // punto exacto de impacto en coordenadas del mundo
+    pub cell: (usize, usize),  // (columna, fila) de la celda golpeada
+    pub side: WallSide,
+    pub normal: Vector2,       // normal de la cara golpeada
+    pub tex_u: f32,            // coordenada horizontal de textura en [0, 1)
+    // Para consultas de juego (línea de visión, selección)
+    #[allow(dead_code)]
+    pub dir: Vector2,          // dirección unitaria del rayo
+    #[allow(dead_code)]
+    pub cells_traversed: u32,  // celdas que visitó el DDA, de la de origen a la golpeada
+}
+
+impl Intersect {
+    /// Indica si el rayo golpeó una pared antes de la distancia máxima
+    pub fn is_hit(&self) -> bool {
+        self.impact != ' '
+    }
+}
+
+/// Recorrido DDA de un rayo celda por celda. Trabaja en coordenadas de
+/// celda (1.0 = una celda).
+struct Dda {
+    pos_x: f32,
+    pos_y: f32,
+    cos_a: f32,
+    sin_a: f32,
+    map_x: i32,
+    map_y: i32,
+    delta_x: f32,
+    delta_y: f32,
+    step_x: i32,
+    step_y: i32,
+    side_dist_x: f32,
+    side_dist_y: f32,
+    started: bool,
+}
+
+impl Dda {
+    fn new(origin: Vector2, a: f32, block: f32) -> Self {
+        // Pre-calcular cos y sin para evitar recálculos
+        let cos_a = a.cos();
+        let sin_a = a.sin();
+
+        let pos_x = origin.x / block;
+        let pos_y = origin.y / block;
+        let map_x = pos_x.floor() as i32;
+        let map_y = pos_y.floor() as i32;
+
+        // Distancia que recorre el rayo para cruzar una celda completa en cada eje
+        let delta_x = if cos_a == 0.0 { f32::INFINITY } else { (1.0 / cos_a).abs() };
+        let delta_y = if sin_a == 0.0 { f32::INFINITY } else { (1.0 / sin_a).abs() };
+
+        // Dirección del paso y distancia hasta el primer borde de celda
+        let (step_x, side_dist_x) = if cos_a < 0.0 {
+            (-1, (pos_x - map_x as f32) * delta_x)
+        } else {
+            (1, (map_x as f32 + 1.0 - pos_x) * delta_x)
+        };
+        let (step_y, side_dist_y) = if sin_a < 0.0 {
+            (-1, (pos_y - map_y as f32) * delta_y)
+        } else {
+            (1, (map_y as f32 + 1.0 - pos_y) * delta_y)
+        };
+
+        Dda { pos_x, pos_y, cos_a, sin_a, map_x, map_y, delta_x, delta_y, step_x, step_y, side_dist_x, side_dist_y, started: false }
+    }
+
+    /// Pasar a la celda siguiente. Devuelve la distancia a la que el rayo
+    /// entra en ella y la cara que cruza. La primera llamada devuelve la
+    /// celda de partida a distancia 0, porque puede tener paredes delgadas
+    /// o una puerta delante del origen.
+    fn advance(&mut self) -> (f32, WallSide) {
+        if !self.started {
+            self.started = true;
+            return (0.0, WallSide::North);
+        }
+        if self.side_dist_x < self.side_dist_y {
+            let d = self.side_dist_x;
+            self.side_dist_x += self.delta_x;
+            self.map_x += self.step_x;
+            (d, if self.step_x > 0 { WallSide::West } else { WallSide::East })
+        } else {
+            let d = self.side_dist_y;
+            self.side_dist_y += self.delta_y;
+            self.map_y += self.step_y;
+            (d, if self.step_y > 0 { WallSide::North } else { WallSide::South })
+        }
+    }
+
+    /// Distancia a la que el rayo sale de la celda actual
+    fn exit(&self) -> f32 {
+        self.side_dist_x.min(self.side_dist_y)
+    }
+
+    /// Celda actual si está dentro del grid
+    fn cell(&self, maze: &Maze) -> Option<(usize, usize, char)> {
+        if self.map_x < 0 || self.map_y < 0 {
+            return None;
+        }
+        let (col, row) = (self.map_x as usize, self.map_y as usize);
+        maze.get(row)?.get(col).map(|&cell| (col, row, cell))
+    }
+
+    /// Coordenada de textura de la cara que se cruza a distancia `d`,
+    /// orientada para que siempre avance de izquierda a derecha
+    fn face_u(&self, d: f32, side: WallSide) -> f32 {
+        let hit_x = self.pos_x + d * self.cos_a;
+        let hit_y = self.pos_y + d * self.sin_a;
+        match side {
+            WallSide::West => hit_y.fract(),
+            WallSide::East => 1.0 - hit_y.fract(),
+            WallSide::North => 1.0 - hit_x.fract(),
+            WallSide::South => hit_x.fract(),
+        }
+    }
+
+    /// Dónde golpea el rayo a la celda sólida actual, en la que entró a
+    /// distancia `d` por `side`: (distancia, cara, u). Las puertas pueden
+    /// dejarlo pasar.
+    fn solid_hit(&self, maze: &Maze, cell: char, d: f32, side: WallSide) -> Option<(f32, WallSide, f32)> {
+        if is_thin_wall(cell) {
+            return self.segment_hit(maze.wall_segments(self.map_x as usize, self.map_y as usize), d);
+        }
+        if cell != 'D' {
+            // El origen nunca está dentro de un bloque completo
+            return (d > 0.0).then(|| (d, side, self.face_u(d, side)));
+        }
+        // La hoja está hundida a mitad de celda; si el rayo pasa por la
+        // parte abierta sigue avanzando
+        maze.door_at(self.map_x as usize, self.map_y as usize)
+            .and_then(|door| intersect_door(door, self.pos_x, self.pos_y, self.cos_a, self.sin_a, d, self.exit()))
+    }
+
+    /// El tramo más cercano que cruza el rayo después de entrar a la celda
+    /// a distancia `d`: (distancia, cara, u). La cara es la que mira al
+    /// rayo según la normal del tramo.
+    fn segment_hit(&self, segments: &[Segment], d: f32) -> Option<(f32, WallSide, f32)> {
+        let origin = Vector2::new(self.pos_x, self.pos_y);
+        let dir = Vector2::new(self.cos_a, self.sin_a);
+        let (segment, t, s, cross) = segments
+            .iter()
+            .filter_map(|segment| segment.intersect_ray(origin, dir).map(|(t, s, cross)| (segment, t, s, cross)))
+            .filter(|&(_, t, _, _)| t >= d - 1e-4)
+            .min_by(|a, b| a.1.total_cmp(&b.1))?;
+
+        let (ex, ey) = (segment.to.x - segment.from.x, segment.to.y - segment.from.y);
+        // Normal del lado por donde llega el rayo
+        let (nx, ny) = if cross > 0.0 { (-ey, ex) } else { (ey, -ex) };
+        let side = if nx.abs() >= ny.abs() {
+            if nx < 0.0 { WallSide::West } else { WallSide::East }
+        } else if ny < 0.0 {
+            WallSide::North
+        } else {
+            WallSide::South
+        };
+
+        // Los tramos rectos siguen la textura del grid; las diagonales la
+        // estiran de punta a punta
+        let tex_u = if ex == 0.0 || ey == 0.0 {
+            self.face_u(t, side)
+        } else if cross > 0.0 {
+            s
+        } else {
+            1.0 - s
+        };
+        Some((t.max(d), side, tex_u))
+    }
+
+    fn intersect(&self, t: f32, cell: char, side: WallSide, tex_u: f32, block: f32, cells_traversed: u32) -> Intersect {
+        let hit_x = self.pos_x + t * self.cos_a;
+        let hit_y = self.pos_y + t * self.sin_a;
+        Intersect {
+            distance: t * block,
+            impact: cell,
+            hit: Vector2::new(hit_x * block, hit_y * block),
+            cell: (self.map_x as usize, self.map_y as usize),
+            side,
+            normal: side.normal(),
+            tex_u: tex_u.clamp(0.0, 1.0 - f32::EPSILON),
+            dir: Vector2::new(self.cos_a, self.sin_a),
+            cells_traversed,
+        }
+    }
 }
 
+/// Distancia máxima que recorre un rayo, en unidades del mundo
+const MAX_RAY_DISTANCE: f32 = 1000.0;
+
+/// Lanza un rayo desde `origin` (coordenadas del mundo) con ángulo `a`.
+/// Es una consulta pura sobre el maze: sirve tanto para renderizar como
+/// para lógica de juego (línea de visión, oclusión de sonido, picking).
 pub fn cast_ray(
-    framebuffer: &mut Framebuffer,
     maze: &Maze,
-    player: &Player,
-    block_size: usize,
+    origin: Vector2,
     a: f32,
-    init_pos: Vector2,
-    draw_line: bool
+    block_size: usize,
 ) -> Intersect {
-    let mut d = 0.0;
-    let max_distance = 1000.0;
-    let step_size = 2.0; // Incrementado para mejor rendimiento
-    
-    // Pre-calcular color para el rayo
-    let ray_color = rgba_to_u32(212, 255, 247, 100);
-    
-    // Pre-calcular cos y sin para evitar recálculos
-    let cos_a = a.cos();
-    let sin_a = a.sin();
-    let init_x = player.pos.x + init_pos.x;
-    let init_y = player.pos.y + init_pos.y;
+    let block = block_size as f32;
+    let mut dda = Dda::new(origin, a, block);
+    let mut cells_traversed = 0;
+
+    // DDA: avanzar celda por celda hasta golpear algo
+    loop {
+        let (d, side) = dda.advance();
+        cells_traversed += 1;
+
+        if d * block >= MAX_RAY_DISTANCE {
+            break;
+        }
+        let Some((_, _, cell)) = dda.cell(maze) else {
+            break;
+        };
+        if is_open_cell(cell) {
+            continue;
+        }
+        if let Some((t, side, tex_u)) = dda.solid_hit(maze, cell, d, side) {
+            return dda.intersect(t, cell, side, tex_u, block, cells_traversed);
+        }
+    }
 
-    while d < max_distance {
-        let x = init_x + d * cos_a;
-        let y = init_y + d * sin_a;
+    Intersect {
+        distance: MAX_RAY_DISTANCE,
+        impact: ' ',
+        hit: Vector2::new(origin.x + MAX_RAY_DISTANCE * dda.cos_a, origin.y + MAX_RAY_DISTANCE * dda.sin_a),
+        cell: (0, 0),
+        side: WallSide::North,
+        normal: Vector2::new(0.0, 0.0),
+        tex_u: 0.0,
+        dir: Vector2::new(dda.cos_a, dda.sin_a),
+        cells_traversed,
+    }
+}
+
+/// Celda que cruza un rayo donde cambia la altura respecto de la anterior:
+/// paredes (de cualquier alto), hojas de puerta, fosos y el suelo que
+/// sigue a un foso
+pub struct RayStep {
+    pub hit: Intersect,  // entrada a la celda (o a la hoja de la puerta)
+    pub exit: f32,       // distancia a la que el rayo sale de la celda
+    pub height: f32,     // altura de la celda (ver `Maze::height_at`)
+    pub previous: f32,   // altura de la celda por la que venía el rayo
+}
 
-        let maze_x = x as usize / block_size;
-        let maze_y = y as usize / block_size;
+/// Seguir un rayo más allá de las paredes bajas y los fosos. Deja en
+/// `steps`, de la más cercana a la más lejana, cada celda donde cambia la
+/// altura, hasta la primera pared de al menos `stop_height` (que tapa todo
+/// lo que hay detrás) o hasta la distancia máxima.
+pub fn cast_ray_steps(
+    maze: &Maze,
+    origin: Vector2,
+    a: f32,
+    block_size: usize,
+    stop_height: f32,
+    steps: &mut Vec<RayStep>,
+) {
+    steps.clear();
+    let block = block_size as f32;
+    let mut dda = Dda::new(origin, a, block);
+    let mut previous = 0.0;
+    let mut cells_traversed = 0;
 
-        if maze_y >= maze.len() || maze_x >= maze[0].len() {
+    loop {
+        let (d, side) = dda.advance();
+        cells_traversed += 1;
+
+        if d * block >= MAX_RAY_DISTANCE {
+            break;
+        }
+        let Some((col, row, cell)) = dda.cell(maze) else {
+            break;
+        };
+
+        let solid = if is_open_cell(cell) { None } else { dda.solid_hit(maze, cell, d, side) };
+        let Some((t, side, tex_u)) = solid else {
+            // Suelo (o puerta abierta o pared delgada por donde pasa el
+            // rayo): solo importa si cambia de nivel, al entrar o salir de
+            // un foso
+            let height = if is_open_cell(cell) { maze.height_at(col, row) } else { 0.0 };
+            if height != previous {
+                let hit = dda.intersect(d, cell, side, dda.face_u(d, side), block, cells_traversed);
+                steps.push(RayStep { hit, exit: dda.exit() * block, height, previous });
+                previous = height;
+            }
+            continue;
+        };
+
+        let hit = dda.intersect(t, cell, side, tex_u, block, cells_traversed);
+        if cell == 'D' || is_thin_wall(cell) {
+            // Las hojas y las paredes delgadas no tienen grosor: se entra y
+            // se sale en el mismo punto
+            let height = if cell == 'D' { 1.0 } else { maze.height_at(col, row) };
+            steps.push(RayStep { exit: hit.distance, hit, height, previous });
+            previous = 0.0;
+            if height >= stop_height {
+                break;
+            }
+            continue;
+        }
+
+        let height = maze.height_at(col, row);
+        steps.push(RayStep { hit, exit: dda.exit() * block, height, previous });
+        previous = height;
+        if height >= stop_height {
             break;
         }
+    }
+}
 
-        if maze[maze_y][maze_x] != ' ' {
-            return Intersect {
-                distance: d,
-                impact: maze[maze_y][maze_x]
-            };
+/// Intersección con la hoja de una puerta, ubicada en el plano medio de su
+/// celda. `enter` y `exit` son las distancias (en celdas) a las que el rayo
+/// entra y sale de la celda. Devuelve (distancia, cara, u) si la golpea.
+fn intersect_door(
+    door: &Door,
+    pos_x: f32,
+    pos_y: f32,
+    cos_a: f32,
+    sin_a: f32,
+    enter: f32,
+    exit: f32,
+) -> Option<(f32, WallSide, f32)> {
+    let (t, along, side) = if door.horizontal {
+        if sin_a == 0.0 {
+            return None;
+        }
+        let t = (door.row as f32 + 0.5 - pos_y) / sin_a;
+        let side = if sin_a > 0.0 { WallSide::North } else { WallSide::South };
+        (t, pos_x + t * cos_a - door.col as f32, side)
+    } else {
+        if cos_a == 0.0 {
+            return None;
         }
+        let t = (door.col as f32 + 0.5 - pos_x) / cos_a;
+        let side = if cos_a > 0.0 { WallSide::West } else { WallSide::East };
+        (t, pos_y + t * sin_a - door.row as f32, side)
+    };
+
+    if t < enter || t > exit || !(0.0..1.0).contains(&along) {
+        return None;
+    }
+
+    // La hoja se desliza: la parte ya abierta deja pasar el rayo
+    if along < door.open_amount {
+        return None;
+    }
 
-        if draw_line {
-            framebuffer.set_pixel_fast(x as u32, y as u32, ray_color);
+    Some((t, side, along - door.open_amount))
+}
+
+/// Dibuja un rayo ya trazado en una vista 2D (mapa completo o minimapa).
+/// `offset` es la esquina del mapa en pantalla y `scale` la relación entre
+/// el tamaño de celda dibujado y el del mundo.
+pub fn draw_ray(
+    framebuffer: &mut Framebuffer,
+    origin: Vector2,
+    intersect: &Intersect,
+    offset: Vector2,
+    scale: f32,
+) {
+    let ray_color = rgba_to_u32(212, 255, 247, 100);
+    let impact_color = rgba_to_u32(255, 255, 0, 255);
+
+    let from = Vector2::new(origin.x * scale + offset.x, origin.y * scale + offset.y);
+    let to = Vector2::new(intersect.hit.x * scale + offset.x, intersect.hit.y * scale + offset.y);
+    framebuffer.draw_line_fast(from, to, ray_color);
+
+    // Marcar el punto exacto de impacto
+    if intersect.is_hit() {
+        let hit_x = to.x as i32;
+        let hit_y = to.y as i32;
+        for (dx, dy) in [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let (x, y) = (hit_x + dx, hit_y + dy);
+            if x >= 0 && y >= 0 {
+                framebuffer.set_pixel_fast(x as u32, y as u32, impact_color);
+            }
         }
-        
-        d += step_size;
     }
-    
-    Intersect { distance: max_distance, impact: ' ' }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    /// Sala de 3x3 celdas con `wall` en la del centro, (2, 2)
+    fn room(wall: char) -> Maze {
+        let mut cells: Vec<Vec<char>> = ["#####", "#   #", "#   #", "#   #", "#####"]
+            .iter()
+            .map(|row| row.chars().collect())
+            .collect();
+        cells[2][2] = wall;
+        Maze::new(cells)
+    }
+
+    fn cell_point(x: f32, y: f32) -> Vector2 {
+        Vector2::new(x * 30.0, y * 30.0)
+    }
+
+    #[test]
+    fn rays_hit_thin_walls_on_their_segment() {
+        let hit = cast_ray(&room('-'), cell_point(2.25, 3.5), -FRAC_PI_2, 30);
+        assert_eq!((hit.impact, hit.cell, hit.side), ('-', (2, 2), WallSide::South));
+        assert!((hit.distance - 30.0).abs() < 1e-3 && (hit.tex_u - 0.25).abs() < 1e-3);
+
+        let hit = cast_ray(&room('|'), cell_point(1.5, 2.75), 0.0, 30);
+        assert_eq!((hit.impact, hit.cell, hit.side), ('|', (2, 2), WallSide::West));
+        assert!((hit.distance - 30.0).abs() < 1e-3 && (hit.tex_u - 0.75).abs() < 1e-3);
+
+        // La diagonal va de (2, 3) a (3, 2): la textura se estira a lo largo
+        let hit = cast_ray(&room('/'), cell_point(2.25, 3.5), -FRAC_PI_2, 30);
+        assert_eq!((hit.impact, hit.cell), ('/', (2, 2)));
+        assert!((hit.distance - 22.5).abs() < 1e-3 && (hit.tex_u - 0.25).abs() < 1e-3);
+    }
+
+    #[test]
+    fn rays_along_a_thin_wall_pass_by() {
+        let hit = cast_ray(&room('-'), cell_point(1.5, 2.5), 0.0, 30);
+        assert_eq!((hit.impact, hit.cell), ('#', (4, 2)));
+        assert!((hit.distance - 75.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn hits_record_the_ray_direction_and_cells_crossed() {
+        let hit = cast_ray(&room('-'), cell_point(1.5, 2.5), 0.0, 30);
+        assert!((hit.dir.x - 1.0).abs() < 1e-6 && hit.dir.y.abs() < 1e-6);
+        assert_eq!(hit.cells_traversed, 4);
+
+        let hit = cast_ray(&room('-'), cell_point(2.25, 3.5), -FRAC_PI_2, 30);
+        assert!(hit.dir.x.abs() < 1e-6 && (hit.dir.y + 1.0).abs() < 1e-6);
+        assert_eq!(hit.cells_traversed, 2);
+    }
+}
@@ -0,0 +1,44 @@
+/// Reloj del juego con paso fijo. Acumula el tiempo real de cada frame y lo
+/// reparte en pasos de simulación de duración constante, así la velocidad
+/// del juego no depende de los FPS.
+pub struct GameClock {
+    pub time: f32,      // segundos de juego simulados (los suma cada paso)
+    pub paused: bool,
+    accumulator: f32,   // tiempo real pendiente de simular
+}
+
+impl GameClock {
+    /// Duración de un paso de simulación en segundos
+    pub const STEP: f32 = 1.0 / 60.0;
+    /// Tiempo máximo por frame, para no quedar atrapado simulando tras un tirón
+    const MAX_FRAME_TIME: f32 = 0.25;
+
+    pub fn new() -> Self {
+        GameClock {
+            time: 0.0,
+            paused: false,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Sumar el tiempo real del frame y devolver cuántos pasos simular
+    pub fn advance(&mut self, frame_time: f32) -> u32 {
+        if self.paused {
+            return 0;
+        }
+        self.accumulator += frame_time.min(Self::MAX_FRAME_TIME);
+        let steps = (self.accumulator / Self::STEP) as u32;
+        self.accumulator -= steps as f32 * Self::STEP;
+        steps
+    }
+
+    /// Fracción del siguiente paso ya transcurrida, para interpolar el dibujo
+    /// entre el estado anterior y el actual
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / Self::STEP).clamp(0.0, 1.0)
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+}
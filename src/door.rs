@@ -0,0 +1,103 @@
+use crate::math::{Segment, Vector2};
+
+/// Estado de una puerta corrediza estilo Wolfenstein
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DoorState {
+    Closed,
+    Opening,
+    Open,
+    Closing,
+}
+
+#[derive(Clone, Debug)]
+pub struct Door {
+    pub col: usize,
+    pub row: usize,
+    pub horizontal: bool,  // true si la hoja corre a lo largo del eje X
+    pub open_amount: f32,  // 0.0 = cerrada, 1.0 = totalmente abierta
+    pub state: DoorState,
+    pub timer: f32,        // segundos que lleva abierta
+    pub lock: Option<char>, // id de la llave necesaria, si tiene cerradura
+}
+
+impl Door {
+    /// Segundos que tarda la hoja en abrirse o cerrarse por completo
+    pub const SLIDE_TIME: f32 = 0.8;
+    /// Segundos que la puerta permanece abierta antes de cerrarse sola
+    pub const STAY_OPEN_TIME: f32 = 3.0;
+
+    pub fn new(col: usize, row: usize, horizontal: bool) -> Self {
+        Door {
+            col,
+            row,
+            horizontal,
+            open_amount: 0.0,
+            state: DoorState::Closed,
+            timer: 0.0,
+            lock: None,
+        }
+    }
+
+    /// Solo se puede atravesar cuando la hoja está completamente abierta
+    pub fn is_passable(&self) -> bool {
+        self.state == DoorState::Open
+    }
+
+    /// Hoja cerrada completa, en el plano medio de la celda (coordenadas
+    /// de celda)
+    pub fn leaf(&self) -> Segment {
+        let (col, row) = (self.col as f32, self.row as f32);
+        if self.horizontal {
+            Segment::new(Vector2::new(col, row + 0.5), Vector2::new(col + 1.0, row + 0.5))
+        } else {
+            Segment::new(Vector2::new(col + 0.5, row), Vector2::new(col + 0.5, row + 1.0))
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_some()
+    }
+
+    pub fn open(&mut self) {
+        if self.is_locked() {
+            return;
+        }
+        match self.state {
+            DoorState::Closed | DoorState::Closing => self.state = DoorState::Opening,
+            DoorState::Open => self.timer = 0.0,
+            DoorState::Opening => {}
+        }
+    }
+
+    /// Avanzar la animación. `blocked` indica que algo ocupa la celda y
+    /// la puerta no debe cerrarse todavía.
+    pub fn update(&mut self, dt: f32, blocked: bool) {
+        let step = dt / Self::SLIDE_TIME;
+        match self.state {
+            DoorState::Opening => {
+                self.open_amount = (self.open_amount + step).min(1.0);
+                if self.open_amount >= 1.0 {
+                    self.state = DoorState::Open;
+                    self.timer = 0.0;
+                }
+            }
+            DoorState::Open => {
+                self.timer += dt;
+                if self.timer >= Self::STAY_OPEN_TIME && !blocked {
+                    self.state = DoorState::Closing;
+                }
+            }
+            DoorState::Closing => {
+                if blocked {
+                    self.state = DoorState::Opening;
+                    return;
+                }
+                self.open_amount = (self.open_amount - step).max(0.0);
+                if self.open_amount <= 0.0 {
+                    self.state = DoorState::Closed;
+                }
+            }
+            DoorState::Closed => {}
+        }
+    }
+}
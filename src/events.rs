@@ -1,100 +1,151 @@
-use raylib::prelude::*;
 use std::f32::consts::PI;
+use crate::math::Vector2;
+use crate::platform::{Key, Platform};
 use crate::player::Player;
-use crate::game::Maze;
+use crate::game::{is_thin_wall, Maze};
+use crate::cast::cast_ray;
 
-pub fn process_events(
-    window: &mut RaylibHandle,
-    player: &mut Player,
-    maze: &Maze,
-    last_mouse_x: &mut i32,
-    block_size: usize
-) {
-    // Constantes de movimiento
-    const MOVE_SPEED: f32 = 3.5;
-    const KEYBOARD_ROTATION_SPEED: f32 = PI / 60.0;
-    const MOUSE_SENSITIVITY: f32 = 0.003;
-    const MAX_ROTATION_PER_FRAME: f32 = PI / 20.0;
+/// Buffer de colisión para que el jugador no se pegue exactamente a las paredes
+const COLLISION_BUFFER: f32 = 8.0;
+
+/// Velocidad de movimiento en unidades del mundo por segundo
+const MOVE_SPEED: f32 = 210.0;
+/// Velocidad de giro con teclado en radianes por segundo
+const KEYBOARD_TURN_SPEED: f32 = PI;
+/// Giro lento al dejar el mouse cerca del borde de la ventana, en radianes por segundo
+const EDGE_TURN_SPEED: f32 = 1.5 * PI;
+const MOUSE_SENSITIVITY: f32 = 0.003;
+const MAX_MOUSE_TURN: f32 = PI / 20.0;
+/// Fracción de pantalla que sube o baja el horizonte por píxel de mouse
+const MOUSE_PITCH_SENSITIVITY: f32 = 0.002;
+/// Fracción de la velocidad normal al caminar agachado
+const CROUCH_SPEED_FACTOR: f32 = 0.5;
 
+/// Entrada del jugador acumulada entre pasos de simulación
+#[derive(Clone, Copy, Default, Debug)]
+pub struct PlayerInput {
+    pub forward: f32,     // -1 atrás, 1 adelante
+    pub strafe: f32,      // -1 izquierda, 1 derecha
+    pub turn: f32,        // giro continuo en fracciones de KEYBOARD_TURN_SPEED
+    pub mouse_turn: f32,  // radianes girados con el mouse desde el último paso
+    pub mouse_pitch: f32, // cambio de inclinación con el mouse desde el último paso
+    pub use_door: bool,
+    pub jump: bool,
+    pub crouch: bool,     // se mantiene mientras la tecla esté presionada
+}
+
+/// Leer teclado y mouse del frame actual. Los ejes se reemplazan en cada
+/// frame, mientras que el giro del mouse y las acciones se acumulan hasta
+/// que un paso de simulación los consume.
+pub fn read_input(platform: &dyn Platform, input: &mut PlayerInput, last_mouse_x: &mut i32, last_mouse_y: &mut i32) {
     // === ROTACIÓN CON MOUSE ===
-    let current_mouse_x = window.get_mouse_x();
-    let screen_width = window.get_screen_width();
-    let screen_center_x = screen_width / 2;
-    
+    let current_mouse_x = platform.mouse_x();
+    let screen_width = platform.screen_width();
+
+    let mut edge_turn = 0.0;
     // Inicializar last_mouse_x si es la primera vez
-    if *last_mouse_x == 0 {
-        *last_mouse_x = current_mouse_x;
-        return;
-    }
-    
-    // Calcular delta 
-    let raw_delta = current_mouse_x - *last_mouse_x;
-    let mut clamped_rotation = 0.0;
-
-    // Si hubo un movimiento válido del mouse
-    if raw_delta.abs() > 2 && raw_delta.abs() < 200 {
-        let rotation_amount = raw_delta as f32 * MOUSE_SENSITIVITY;
-        clamped_rotation = rotation_amount.clamp(-MAX_ROTATION_PER_FRAME, MAX_ROTATION_PER_FRAME);
-        // window.set_mouse_position(Vector2::new(screen_center_x as f32, window.get_screen_height() as f32 / 2.0));
-    } else {
-        // Detectar si estamos en una zona cercana al borde
-        let edge_threshold = 150; // píxeles
-        if current_mouse_x <= edge_threshold {
-            clamped_rotation = -MAX_ROTATION_PER_FRAME / 2.0; // rotar a la izquierda lentamente
-        } else if current_mouse_x >= screen_width - edge_threshold {
-            clamped_rotation = MAX_ROTATION_PER_FRAME / 2.0; // rotar a la derecha lentamente
+    if *last_mouse_x != 0 {
+        let raw_delta = current_mouse_x - *last_mouse_x;
+
+        // Si hubo un movimiento válido del mouse
+        if raw_delta.abs() > 2 && raw_delta.abs() < 200 {
+            let rotation_amount = raw_delta as f32 * MOUSE_SENSITIVITY;
+            input.mouse_turn += rotation_amount.clamp(-MAX_MOUSE_TURN, MAX_MOUSE_TURN);
+        } else {
+            // Detectar si estamos en una zona cercana al borde
+            let edge_threshold = 150; // píxeles
+            if current_mouse_x <= edge_threshold {
+                edge_turn = -EDGE_TURN_SPEED / KEYBOARD_TURN_SPEED;
+            } else if current_mouse_x >= screen_width - edge_threshold {
+                edge_turn = EDGE_TURN_SPEED / KEYBOARD_TURN_SPEED;
+            }
         }
     }
-
-    player.a += clamped_rotation;
-    
-    // Actualizar last_mouse_x
     *last_mouse_x = current_mouse_x;
 
+    // === INCLINACIÓN CON MOUSE === (mouse arriba: mirar arriba)
+    let current_mouse_y = platform.mouse_y();
+    if *last_mouse_y != 0 {
+        let raw_delta = current_mouse_y - *last_mouse_y;
+        if raw_delta.abs() > 2 && raw_delta.abs() < 200 {
+            input.mouse_pitch -= raw_delta as f32 * MOUSE_PITCH_SENSITIVITY;
+        }
+    }
+    *last_mouse_y = current_mouse_y;
 
-    // === ROTACIÓN CON TECLADO (más lenta) ===
-    if window.is_key_down(KeyboardKey::KEY_LEFT) {
-        player.a -= KEYBOARD_ROTATION_SPEED;
+    // === ROTACIÓN CON TECLADO ===
+    let mut turn = edge_turn;
+    if platform.is_key_down(Key::Left) {
+        turn -= 1.0;
     }
-    if window.is_key_down(KeyboardKey::KEY_RIGHT) {
-        player.a += KEYBOARD_ROTATION_SPEED;
+    if platform.is_key_down(Key::Right) {
+        turn += 1.0;
     }
-
-    let mut move_x = 0.0;
-    let mut move_y = 0.0;
+    input.turn = turn;
 
     // Movimiento hacia adelante/atrás
-    if window.is_key_down(KeyboardKey::KEY_UP) || window.is_key_down(KeyboardKey::KEY_W) {
-        move_x += MOVE_SPEED * player.a.cos();
-        move_y += MOVE_SPEED * player.a.sin();
+    let mut forward = 0.0;
+    if platform.is_key_down(Key::Up) || platform.is_key_down(Key::W) {
+        forward += 1.0;
     }
-    if window.is_key_down(KeyboardKey::KEY_DOWN) || window.is_key_down(KeyboardKey::KEY_S) {
-        move_x -= MOVE_SPEED * player.a.cos();
-        move_y -= MOVE_SPEED * player.a.sin();
+    if platform.is_key_down(Key::Down) || platform.is_key_down(Key::S) {
+        forward -= 1.0;
     }
+    input.forward = forward;
 
-    // Strafe (movimiento lateral)
-    if window.is_key_down(KeyboardKey::KEY_Q) {
-        // Strafe izquierda (perpendicular a la dirección de vista)
-        move_x += MOVE_SPEED * (player.a - PI/2.0).cos();
-        move_y += MOVE_SPEED * (player.a - PI/2.0).sin();
+    // Strafe (movimiento lateral) con Q/E y A/D
+    let mut strafe = 0.0;
+    if platform.is_key_down(Key::Q) || platform.is_key_down(Key::A) {
+        strafe -= 1.0;
     }
-    if window.is_key_down(KeyboardKey::KEY_E) {
-        // Strafe derecha
-        move_x += MOVE_SPEED * (player.a + PI/2.0).cos();
-        move_y += MOVE_SPEED * (player.a + PI/2.0).sin();
+    if platform.is_key_down(Key::E) || platform.is_key_down(Key::D) {
+        strafe += 1.0;
     }
+    input.strafe = strafe;
 
-    // Mantener A y D solo para strafe
-    if window.is_key_down(KeyboardKey::KEY_A) {
-        // Strafe izquierda
-        move_x += MOVE_SPEED * (player.a - PI/2.0).cos();
-        move_y += MOVE_SPEED * (player.a - PI/2.0).sin();
+    input.crouch = platform.is_key_down(Key::C) || platform.is_key_down(Key::LeftControl);
+
+    if platform.is_key_pressed(Key::F) {
+        input.use_door = true;
+    }
+    if platform.is_key_pressed(Key::Space) {
+        input.jump = true;
     }
-    if window.is_key_down(KeyboardKey::KEY_D) {
-        // Strafe derecha
-        move_x += MOVE_SPEED * (player.a + PI/2.0).cos();
-        move_y += MOVE_SPEED * (player.a + PI/2.0).sin();
+}
+
+/// Aplicar la entrada al jugador durante un paso de simulación de `dt`
+/// segundos. Consume el giro del mouse y las acciones pendientes.
+pub fn update_player(
+    player: &mut Player,
+    maze: &mut Maze,
+    input: &mut PlayerInput,
+    dt: f32,
+    block_size: usize
+) {
+    player.a += input.mouse_turn + input.turn * KEYBOARD_TURN_SPEED * dt;
+    input.mouse_turn = 0.0;
+    player.pitch = (player.pitch + input.mouse_pitch).clamp(-Player::MAX_PITCH, Player::MAX_PITCH);
+    input.mouse_pitch = 0.0;
+
+    // === ALTURA DE LA CÁMARA ===
+    let walking = input.forward != 0.0 || input.strafe != 0.0;
+    player.update_height(dt, input.jump, input.crouch, walking);
+    input.jump = false;
+    let speed = MOVE_SPEED * (1.0 - (1.0 - CROUCH_SPEED_FACTOR) * player.crouch);
+
+    let forward = Vector2::new(player.a.cos(), player.a.sin());
+    let right = Vector2::new((player.a + PI / 2.0).cos(), (player.a + PI / 2.0).sin());
+    let move_x = (forward.x * input.forward + right.x * input.strafe) * speed * dt;
+    let move_y = (forward.y * input.forward + right.y * input.strafe) * speed * dt;
+
+    // === USAR PUERTAS ===
+    if input.use_door {
+        input.use_door = false;
+        let use_distance = 1.5 * block_size as f32;
+        let target = cast_ray(maze, player.pos, player.a, block_size);
+        if target.impact == 'D' && target.distance < use_distance {
+            maze.use_door(target.cell.0, target.cell.1, &mut player.keys);
+        }
     }
 
     // === SISTEMA DE COLISIÓN ===
@@ -121,21 +172,21 @@ pub fn process_events(
     }
 }
 
-/// Verifica si el jugador puede moverse a esa posición con un pequeño buffer
-fn can_move_to(x: f32, y: f32, maze: &Maze, block_size: usize) -> bool {
-    // Buffer de colisión para que el jugador no se pegue exactamente a las paredes
-    let collision_buffer = 8.0;
-    
-    // Verificar múltiples puntos alrededor del jugador (hitbox)
-    let check_points = [
-        (x - collision_buffer, y - collision_buffer), // Esquina superior izquierda
-        (x + collision_buffer, y - collision_buffer), // Esquina superior derecha
-        (x - collision_buffer, y + collision_buffer), // Esquina inferior izquierda
-        (x + collision_buffer, y + collision_buffer), // Esquina inferior derecha
+/// Puntos de la hitbox del jugador centrada en (x, y)
+fn hitbox_points(x: f32, y: f32) -> [(f32, f32); 5] {
+    [
+        (x - COLLISION_BUFFER, y - COLLISION_BUFFER), // Esquina superior izquierda
+        (x + COLLISION_BUFFER, y - COLLISION_BUFFER), // Esquina superior derecha
+        (x - COLLISION_BUFFER, y + COLLISION_BUFFER), // Esquina inferior izquierda
+        (x + COLLISION_BUFFER, y + COLLISION_BUFFER), // Esquina inferior derecha
         (x, y), // Centro
-    ];
+    ]
+}
 
-    for (check_x, check_y) in check_points.iter() {
+/// Verifica si el jugador puede moverse a esa posición con un pequeño buffer
+pub fn can_move_to(x: f32, y: f32, maze: &Maze, block_size: usize) -> bool {
+    // Verificar múltiples puntos alrededor del jugador (hitbox)
+    for (check_x, check_y) in hitbox_points(x, y).iter() {
         let col = (*check_x as usize) / block_size;
         let row = (*check_y as usize) / block_size;
 
@@ -143,10 +194,105 @@ fn can_move_to(x: f32, y: f32, maze: &Maze, block_size: usize) -> bool {
             return false; // Fuera de límites
         }
 
-        if maze[row][col] != ' ' {
-            return false; // Colisión con pared
+        // Las paredes delgadas y las puertas solo frenan con sus tramos
+        if is_thin_wall(maze[row][col]) || maze.door_at(col, row).is_some() {
+            continue;
+        }
+        if !maze.is_walkable(col, row) {
+            return false; // Colisión con pared o foso
+        }
+    }
+
+    // Ningún tramo de las celdas que toca la hitbox puede quedar más cerca
+    // que el buffer
+    let block = block_size as f32;
+    let center = Vector2::new(x / block, y / block);
+    let radius = COLLISION_BUFFER / block;
+    let (first_col, last_col) = (((x - COLLISION_BUFFER) / block) as usize, ((x + COLLISION_BUFFER) / block) as usize);
+    let (first_row, last_row) = (((y - COLLISION_BUFFER) / block) as usize, ((y + COLLISION_BUFFER) / block) as usize);
+    for row in first_row..=last_row {
+        for col in first_col..=last_col {
+            if maze.blocking_segments(col, row).any(|segment| segment.distance_to(center) < radius) {
+                return false;
+            }
         }
     }
 
     true
-}
\ No newline at end of file
+}
+
+/// Indica si la hitbox del jugador toca la celda (col, row)
+pub fn occupies_cell(pos: Vector2, col: usize, row: usize, block_size: usize) -> bool {
+    hitbox_points(pos.x, pos.y).iter().any(|(x, y)| {
+        (*x as usize) / block_size == col && (*y as usize) / block_size == row
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::Framebuffer;
+    use crate::math::Color;
+    use crate::door::DoorState;
+    use crate::platform::headless::HeadlessPlatform;
+
+    fn maze(rows: &[&str]) -> Maze {
+        Maze::new(rows.iter().map(|row| row.chars().collect()).collect())
+    }
+
+    #[test]
+    fn read_input_follows_keys_and_mouse() {
+        let mut platform = HeadlessPlatform::new(930, 630);
+        let (mut last_x, mut last_y) = (platform.mouse_x(), platform.mouse_y());
+        platform.hold(Key::W);
+        platform.hold(Key::D);
+        platform.tap(Key::F);
+        platform.set_mouse_x(last_x + 20);
+        platform.set_mouse_y(last_y - 10);
+
+        let mut input = PlayerInput::default();
+        read_input(&platform, &mut input, &mut last_x, &mut last_y);
+        assert_eq!((input.forward, input.strafe), (1.0, 1.0));
+        assert!(input.use_door);
+        assert!(input.mouse_turn > 0.0);
+        assert!(input.mouse_pitch > 0.0);
+
+        // Las pulsaciones duran un frame; las teclas mantenidas siguen hasta soltarlas
+        platform.present(&Framebuffer::new(1, 1, Color::BLACK));
+        platform.release(Key::D);
+        let mut input = PlayerInput::default();
+        read_input(&platform, &mut input, &mut last_x, &mut last_y);
+        assert_eq!((input.forward, input.strafe), (1.0, 0.0));
+        assert!(!input.use_door);
+        assert_eq!((input.mouse_turn, input.mouse_pitch), (0.0, 0.0));
+    }
+
+    #[test]
+    fn thin_walls_block_only_their_segment() {
+        // La pared '-' cruza la celda (2, 2) por el medio, en y = 75
+        let maze = maze(&["#####", "#   #", "# - #", "#   #", "#####"]);
+        assert!(!can_move_to(75.0, 70.0, &maze, 30));
+        assert!(!can_move_to(75.0, 80.0, &maze, 30));
+        // Las dos mitades libres de la celda se pueden pisar
+        assert!(can_move_to(75.0, 65.0, &maze, 30));
+        assert!(can_move_to(75.0, 85.0, &maze, 30));
+        // Junto a la pared pero lejos del tramo
+        assert!(can_move_to(50.0, 75.0, &maze, 30));
+    }
+
+    #[test]
+    fn only_open_doors_can_be_crossed() {
+        let mut maze = maze(&["#####", "#   #", "##D##", "#   #", "#####"]);
+        assert!(!can_move_to(75.0, 72.0, &maze, 30));
+        assert!(can_move_to(75.0, 62.0, &maze, 30));
+
+        maze.doors[0].state = DoorState::Opening;
+        maze.doors[0].open_amount = 0.9;
+        assert!(!can_move_to(75.0, 72.0, &maze, 30));
+
+        maze.doors[0].state = DoorState::Open;
+        maze.doors[0].open_amount = 1.0;
+        assert!(can_move_to(75.0, 72.0, &maze, 30));
+        assert!(can_move_to(75.0, 75.0, &maze, 30));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::framebuffer::Framebuffer;
+
+/// Fuente bitmap de 5x7 píxeles para dibujar texto directamente en el
+/// framebuffer (HUD, capturas y renderizado sin ventana)
+pub const GLYPH_WIDTH: u32 = 5;
+pub const GLYPH_HEIGHT: u32 = 7;
+
+/// Cada glifo son 7 filas de 5 bits (el bit más alto es la columna izquierda)
+const GLYPHS: &[(char, [u8; 7])] = &[
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    (' ', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('+', [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000]),
+    ('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+    (':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    (',', [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000]),
+    ('!', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100]),
+    ('/', [0b00001, 0b00010, 0b00010, 0b00100, 0b01000, 0b01000, 0b10000]),
+    ('(', [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010]),
+    (')', [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000]),
+    ('%', [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011]),
+    ('>', [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000]),
+    ('<', [0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010]),
+    ('|', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('\'', [0b00100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('=', [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000]),
+    ('_', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111]),
+];
+
+/// Normalizar un carácter a uno disponible en la fuente
+fn normalize(ch: char) -> char {
+    match ch {
+        'á' | 'Á' => 'A',
+        'é' | 'É' => 'E',
+        'í' | 'Í' => 'I',
+        'ó' | 'Ó' => 'O',
+        'ú' | 'Ú' | 'ü' | 'Ü' => 'U',
+        'ñ' | 'Ñ' => 'N',
+        '¡' => '!',
+        '¿' => '?',
+        c => c.to_ascii_uppercase(),
+    }
+}
+
+fn glyph(ch: char) -> &'static [u8; 7] {
+    let ch = normalize(ch);
+    GLYPHS
+        .iter()
+        .find(|(c, _)| *c == ch)
+        .or_else(|| GLYPHS.iter().find(|(c, _)| *c == '?'))
+        .map(|(_, rows)| rows)
+        .unwrap()
+}
+
+/// Ancho en píxeles de un texto dibujado con `draw_text`
+pub fn text_width(text: &str, scale: u32) -> u32 {
+    let count = text.chars().count() as u32;
+    if count == 0 {
+        return 0;
+    }
+    (count * (GLYPH_WIDTH + 1) - 1) * scale
+}
+
+/// Dibujar texto con la esquina superior izquierda en (x, y)
+pub fn draw_text(framebuffer: &mut Framebuffer, text: &str, x: i32, y: i32, scale: u32, color: u32) {
+    let scale = scale.max(1) as i32;
+    let mut cursor_x = x;
+
+    for ch in text.chars() {
+        let rows = glyph(ch);
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..GLYPH_WIDTH as i32 {
+                if bits & (1 << (GLYPH_WIDTH as i32 - 1 - col)) == 0 {
+                    continue;
+                }
+                let px = cursor_x + col * scale;
+                let py = y + row as i32 * scale;
+                framebuffer.fill_rect(px, py, scale as u32, scale as u32, color);
+            }
+        }
+        cursor_x += (GLYPH_WIDTH as i32 + 1) * scale;
+    }
+}
+
+/// Dibujar texto con un contorno de 1 píxel (escalado) para que se lea
+/// sobre cualquier fondo
+pub fn draw_text_outlined(
+    framebuffer: &mut Framebuffer,
+    text: &str,
+    x: i32,
+    y: i32,
+    scale: u32,
+    color: u32,
+    outline: u32,
+) {
+    let offset = scale.max(1) as i32;
+    for (dx, dy) in [(-offset, 0), (offset, 0), (0, -offset), (0, offset)] {
+        draw_text(framebuffer, text, x + dx, y + dy, scale, outline);
+    }
+    draw_text(framebuffer, text, x, y, scale, color);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Color;
+
+    #[test]
+    fn text_outside_the_framebuffer_is_clipped() {
+        let mut framebuffer = Framebuffer::new(40, 20, Color::BLACK);
+        let background = framebuffer.pixels()[0];
+        draw_text(&mut framebuffer, "FUERA", 45, 2, 2, 0xFFFFFFFF);
+        draw_text(&mut framebuffer, "FUERA", 2, 25, 2, 0xFFFFFFFF);
+        draw_text_outlined(&mut framebuffer, "FUERA", -200, -200, 3, 0xFFFFFFFF, 0xFF000000);
+        assert!(framebuffer.pixels().iter().all(|&pixel| pixel == background));
+
+        // Texto que empieza dentro y sigue más allá del borde derecho
+        draw_text(&mut framebuffer, "BORDE", 30, 2, 2, 0xFFFFFFFF);
+        assert!(framebuffer.pixels().iter().any(|&pixel| pixel != background));
+    }
+}
@@ -1,11 +1,11 @@
-use raylib::prelude::*;
+use crate::math::{Color, Vector2};
 
 pub struct Framebuffer {
     pub width: u32,
     pub height: u32,
     pixel_buffer: Vec<u32>, // RGBA como u32
-    texture: Option<Texture2D>, // Textura reutilizable
     background_color: Color,
+    #[allow(dead_code)]
     current_color: Color,
 }
 
@@ -18,7 +18,6 @@ impl Framebuffer {
             width,
             height,
             pixel_buffer: vec![bg_rgba; size],
-            texture: None,
             background_color,
             current_color: Color::WHITE,
         }
@@ -30,6 +29,7 @@ impl Framebuffer {
     }
 
     #[inline]
+    #[allow(dead_code)]
     pub fn set_pixel(&mut self, x: u32, y: u32) {
         if x < self.width && y < self.height {
             let index = (y * self.width + x) as usize;
@@ -46,54 +46,45 @@ impl Framebuffer {
         }
     }
 
+    #[allow(dead_code)]
     pub fn set_background_color(&mut self, color: Color) {
         self.background_color = color;
     }
 
+    #[allow(dead_code)]
     pub fn set_current_color(&mut self, color: Color) {
         self.current_color = color;
     }
 
-    // Función para crear/actualizar la textura solo cuando sea necesario
-    fn ensure_texture(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
-        if self.texture.is_none() {
-            // Crear textura vacía
-            let img = Image::gen_image_color(self.width as i32, self.height as i32, Color::WHITE);
-            self.texture = Some(rl.load_texture_from_image(thread, &img)
-                .expect("No se pudo crear textura"));
+    /// Píxeles en formato RGBA (u32 por píxel, fila por fila) para que
+    /// el backend los presente o guarde
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixel_buffer
+    }
+
+    /// Copiar una franja de columnas dibujada aparte (`strip` tiene
+    /// `width` píxeles por fila y todas las filas de la pantalla)
+    pub fn copy_strip(&mut self, x: u32, width: u32, strip: &[u32]) {
+        let width = width.min(self.width.saturating_sub(x)) as usize;
+        let stride = strip.len() / self.height.max(1) as usize;
+        for y in 0..self.height as usize {
+            let row = y * self.width as usize + x as usize;
+            self.pixel_buffer[row..row + width].copy_from_slice(&strip[y * stride..y * stride + width]);
         }
     }
 
-    pub fn swap_buffers<F: FnOnce(&mut RaylibDrawHandle)>(
-        &mut self, // Cambiado a &mut para poder modificar la textura
-        window: &mut RaylibHandle,
-        raylib_thread: &RaylibThread,
-        draw_overlay: F
-    ) {
-        // Asegurar que tenemos una textura
-        self.ensure_texture(window, raylib_thread);
-        
-        if let Some(ref mut texture) = self.texture {
-            // Actualizar los datos de la textura existente
-            unsafe {
-                // Convertir nuestro buffer a formato que raylib entienda
-                let raw_data = std::slice::from_raw_parts(
-                    self.pixel_buffer.as_ptr() as *const u8,
-                    self.pixel_buffer.len() * 4
-                );
-                
-                // Actualizar textura existente (mucho más rápido que crear nueva)
-                raylib::ffi::UpdateTexture(*texture.as_ref(), raw_data.as_ptr() as *const _);
-            }
+    /// Píxeles para que los renderizadores escriban filas completas
+    pub fn pixels_mut(&mut self) -> &mut [u32] {
+        &mut self.pixel_buffer
+    }
 
-            let mut d = window.begin_drawing(raylib_thread);
-            d.clear_background(self.background_color);
-            d.draw_texture(texture, 0, 0, Color::WHITE);
-            draw_overlay(&mut d);
-        }
+    #[allow(dead_code)] // solo la usa la ventana para limpiar la pantalla
+    pub fn background_color(&self) -> Color {
+        self.background_color
     }
 
     // Algoritmo de línea optimizado (Bresenham)
+    #[allow(dead_code)]
     pub fn draw_line(&mut self, from: Vector2, to: Vector2, color: Color) {
         let color_rgba = color_to_u32(color);
         self.draw_line_fast(from, to, color_rgba);
@@ -132,7 +123,46 @@ impl Framebuffer {
         }
     }
 
+    /// Rellenar un rectángulo, recortado a los bordes del framebuffer
+    pub fn fill_rect(&mut self, x: i32, y: i32, w: u32, h: u32, color_rgba: u32) {
+        let x0 = x.max(0) as u32;
+        let y0 = y.max(0) as u32;
+        let x1 = ((x + w as i32).max(0) as u32).min(self.width);
+        let y1 = ((y + h as i32).max(0) as u32).min(self.height);
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+
+        for py in y0..y1 {
+            let row = (py * self.width) as usize;
+            self.pixel_buffer[row + x0 as usize..row + x1 as usize].fill(color_rgba);
+        }
+    }
+
+    /// Mezclar un color sobre un rectángulo con opacidad `alpha` (0.0 - 1.0)
+    pub fn blend_rect(&mut self, x: i32, y: i32, w: u32, h: u32, color_rgba: u32, alpha: f32) {
+        let x0 = x.max(0) as u32;
+        let y0 = y.max(0) as u32;
+        let x1 = ((x + w as i32).max(0) as u32).min(self.width);
+        let y1 = ((y + h as i32).max(0) as u32).min(self.height);
+
+        let mix = |dst: u32, shift: u32| {
+            let d = ((dst >> shift) & 0xFF) as f32;
+            let s = ((color_rgba >> shift) & 0xFF) as f32;
+            ((d + (s - d) * alpha) as u32) << shift
+        };
+
+        for py in y0..y1 {
+            for px in x0..x1 {
+                let index = (py * self.width + px) as usize;
+                let dst = self.pixel_buffer[index];
+                self.pixel_buffer[index] = mix(dst, 0) | mix(dst, 8) | mix(dst, 16) | (dst & 0xFF000000);
+            }
+        }
+    }
+
     // Función para dibujar columnas verticales (común en raycasting)
+    #[allow(dead_code)]
     pub fn draw_vertical_line(&mut self, x: u32, y_start: u32, y_end: u32, color_rgba: u32) {
         if x >= self.width {
             return;
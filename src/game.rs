@@ -1,133 +1,681 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use raylib::prelude::*;
+use std::fmt;
+use std::fs;
+use std::ops::Deref;
 
 use crate::framebuffer::{Framebuffer, rgba_to_u32};
+use crate::math::{Color, Segment, Vector2};
 use crate::player::Player;
-use crate::cast::cast_ray;
-use crate::sprites::Sprite; 
+use crate::cast::{cast_ray, draw_ray};
+use crate::sprites::{Sprite, SpriteKind, SPRITE_KINDS, sprite_kind, sprite_kind_by_name, KEY_SYMBOL};
+use crate::door::Door;
 
-pub type Maze = Vec<Vec<char>>;
+/// Llave declarada en el archivo del nivel con
+/// `@key <carácter llave> <carácter puerta> <nombre> <r>,<g>,<b>`
+#[derive(Clone, Debug)]
+pub struct KeyDef {
+    pub id: char,
+    pub door: char,
+    pub name: String,
+    pub color: u32,
+}
+
+impl KeyDef {
+    fn parse(args: &str) -> Option<KeyDef> {
+        let mut parts = args.split_whitespace();
+        let id = parts.next()?.chars().next()?;
+        let door = parts.next()?.chars().next()?;
+        let name = parts.next()?.to_string();
+        let mut rgb = parts.next()?.split(',').map(|c| c.trim().parse::<u8>());
+        let (r, g, b) = (rgb.next()?.ok()?, rgb.next()?.ok()?, rgb.next()?.ok()?);
+        Some(KeyDef { id, door, name, color: rgba_to_u32(r, g, b, 255) })
+    }
+}
+
+/// Texturas de suelo y techo propias de una celda. Son índices en
+/// `Level::surface_textures`; `None` usa la textura general del nivel.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct CellSurface {
+    pub floor: Option<usize>,
+    pub ceiling: Option<usize>,
+}
+
+/// Grid del nivel junto con el estado dinámico de sus celdas (puertas).
+/// Se puede indexar como `maze[fila][columna]` igual que el grid crudo.
+#[derive(Clone)]
+pub struct Maze {
+    cells: Vec<Vec<char>>,
+    pub doors: Vec<Door>,
+    pub keys: Vec<KeyDef>,
+    surfaces: Vec<Vec<CellSurface>>,
+    heights: Vec<Vec<f32>>,
+    segments: Vec<Vec<Vec<Segment>>>,
+}
+
+impl Maze {
+    pub fn new(cells: Vec<Vec<char>>) -> Self {
+        let mut doors = Vec::new();
+        for (row, line) in cells.iter().enumerate() {
+            for (col, &ch) in line.iter().enumerate() {
+                if ch == 'D' {
+                    doors.push(Door::new(col, row, is_horizontal_door(&cells, col, row)));
+                }
+            }
+        }
+        let surfaces = cells.iter().map(|line| vec![CellSurface::default(); line.len()]).collect();
+        let heights = cells
+            .iter()
+            .map(|line| line.iter().map(|&c| if is_open_cell(c) { 0.0 } else { 1.0 }).collect())
+            .collect();
+        let segments = (0..cells.len())
+            .map(|row| (0..cells[row].len()).map(|col| thin_wall_segments(&cells, col, row)).collect())
+            .collect();
+        Maze { cells, doors, keys: Vec::new(), surfaces, heights, segments }
+    }
+
+    /// Altura de la celda en fracciones del alto de pared: la de la pared
+    /// en celdas sólidas y el nivel del suelo en las libres (negativo en
+    /// los fosos). Fuera del grid es una pared normal.
+    #[inline]
+    pub fn height_at(&self, col: usize, row: usize) -> f32 {
+        self.heights.get(row).and_then(|r| r.get(col)).copied().unwrap_or(1.0)
+    }
+
+    /// Altura de la pared más alta del nivel
+    pub fn max_wall_height(&self) -> f32 {
+        self.heights.iter().flatten().copied().fold(1.0, f32::max)
+    }
+
+    /// La celda libre es un foso (se ve el hueco pero no se puede pisar)
+    pub fn is_pit(&self, col: usize, row: usize) -> bool {
+        self.cells.get(row).and_then(|r| r.get(col)).is_some_and(|&c| is_open_cell(c)) && self.height_at(col, row) < 0.0
+    }
+
+    /// Tramos de pared delgada de la celda, en coordenadas de celda (vacío
+    /// en las celdas libres y en los bloques completos)
+    #[inline]
+    pub fn wall_segments(&self, col: usize, row: usize) -> &[Segment] {
+        self.segments.get(row).and_then(|r| r.get(col)).map_or(&[], |s| s.as_slice())
+    }
+
+    /// Tramos que frenan al jugador en la celda: las paredes delgadas y la
+    /// hoja de una puerta que no está abierta del todo
+    pub fn blocking_segments(&self, col: usize, row: usize) -> impl Iterator<Item = Segment> + '_ {
+        let leaf = self.door_at(col, row).filter(|d| !d.is_passable()).map(Door::leaf);
+        self.wall_segments(col, row).iter().copied().chain(leaf)
+    }
+
+    /// Texturas de suelo y techo de la celda (las generales fuera del grid)
+    #[inline]
+    pub fn surface_at(&self, col: usize, row: usize) -> CellSurface {
+        self.surfaces.get(row).and_then(|r| r.get(col)).copied().unwrap_or_default()
+    }
+
+    pub fn door_at(&self, col: usize, row: usize) -> Option<&Door> {
+        self.doors.iter().find(|d| d.col == col && d.row == row)
+    }
+
+    /// Celda que contiene el punto (x, y) en coordenadas del mundo
+    pub fn cell_at(&self, x: f32, y: f32, block_size: usize) -> Option<char> {
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let col = x as usize / block_size;
+        let row = y as usize / block_size;
+        self.cells.get(row).and_then(|r| r.get(col)).copied()
+    }
+
+    pub fn key_def(&self, id: char) -> Option<&KeyDef> {
+        self.keys.iter().find(|k| k.id == id)
+    }
+
+    /// Indica si el jugador puede ocupar la celda
+    pub fn is_walkable(&self, col: usize, row: usize) -> bool {
+        match self.cells.get(row).and_then(|r| r.get(col)) {
+            Some(&c) if is_open_cell(c) => !self.is_pit(col, row),
+            Some('D') => self.door_at(col, row).is_some_and(|d| d.is_passable()),
+            _ => false,
+        }
+    }
+
+    /// Índices de las hojas contiguas a (col, row) que forman una misma
+    /// puerta doble, incluyendo la propia
+    pub fn linked_doors(&self, col: usize, row: usize) -> Vec<usize> {
+        let Some(horizontal) = self.door_at(col, row).map(|d| d.horizontal) else {
+            return Vec::new();
+        };
+
+        let mut linked = Vec::new();
+        for (index, door) in self.doors.iter().enumerate() {
+            let same_line = if horizontal {
+                door.row == row && door.horizontal
+            } else {
+                door.col == col && !door.horizontal
+            };
+            if !same_line {
+                continue;
+            }
+
+            // Solo las hojas pegadas entre sí (sin paredes de por medio)
+            let (from, to) = if horizontal {
+                (door.col.min(col), door.col.max(col))
+            } else {
+                (door.row.min(row), door.row.max(row))
+            };
+            let contiguous = (from..=to).all(|i| {
+                let ch = if horizontal { self.cells[row][i] } else { self.cells[i][col] };
+                ch == 'D'
+            });
+            if contiguous {
+                linked.push(index);
+            }
+        }
+        linked
+    }
+
+    /// Abrir la puerta en (col, row) junto con sus hojas contiguas. Si tiene
+    /// cerradura se consume la llave correspondiente de `keys`; devuelve
+    /// false si el jugador no la tiene.
+    pub fn use_door(&mut self, col: usize, row: usize, keys: &mut Vec<char>) -> bool {
+        let Some(lock) = self.door_at(col, row).map(|d| d.lock) else {
+            return false;
+        };
+        let linked = self.linked_doors(col, row);
+
+        if let Some(key) = lock {
+            let Some(index) = keys.iter().position(|&k| k == key) else {
+                return false;
+            };
+            keys.remove(index);
+            for &i in linked.iter() {
+                if self.doors[i].lock == Some(key) {
+                    self.doors[i].lock = None;
+                }
+            }
+        }
+
+        for &i in linked.iter() {
+            self.doors[i].open();
+        }
+        true
+    }
+
+    /// Animar las puertas. `occupied` indica si algo ocupa la celda de la
+    /// puerta, en cuyo caso no se cierra.
+    pub fn update_doors<F: Fn(&Door) -> bool>(&mut self, dt: f32, occupied: F) {
+        for door in self.doors.iter_mut() {
+            let blocked = occupied(door);
+            door.update(dt, blocked);
+        }
+    }
+}
+
+impl Deref for Maze {
+    type Target = Vec<Vec<char>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.cells
+    }
+}
+
+/// Una puerta corre a lo largo del eje X si tiene paredes a los lados
+fn is_horizontal_door(cells: &[Vec<char>], col: usize, row: usize) -> bool {
+    let is_solid = |c: Option<&char>| c.is_some_and(|&ch| !is_open_cell(ch));
+    let line = &cells[row];
+    is_solid(col.checked_sub(1).and_then(|c| line.get(c))) || is_solid(line.get(col + 1))
+}
+
+/// Tramos de una pared delgada: '-' y '|' cruzan la celda por el medio,
+/// '/' y '\' de esquina a esquina, y '+' une el centro con cada vecino
+/// que no es libre (sin vecinos es una cruz)
+fn thin_wall_segments(cells: &[Vec<char>], col: usize, row: usize) -> Vec<Segment> {
+    let (x, y) = (col as f32, row as f32);
+    let segment = |x0: f32, y0: f32, x1: f32, y1: f32| Segment::new(Vector2::new(x0, y0), Vector2::new(x1, y1));
+    match cells[row][col] {
+        '-' => vec![segment(x, y + 0.5, x + 1.0, y + 0.5)],
+        '|' => vec![segment(x + 0.5, y, x + 0.5, y + 1.0)],
+        '/' => vec![segment(x, y + 1.0, x + 1.0, y)],
+        '\\' => vec![segment(x, y, x + 1.0, y + 1.0)],
+        '+' => {
+            let is_solid = |c: Option<usize>, r: Option<usize>| {
+                let cell = r.and_then(|r| cells.get(r)).and_then(|line| line.get(c?));
+                cell.is_some_and(|&ch| !is_open_cell(ch))
+            };
+            let (cx, cy) = (x + 0.5, y + 0.5);
+            let arms = [
+                (is_solid(col.checked_sub(1), Some(row)), x, cy),
+                (is_solid(Some(col + 1), Some(row)), x + 1.0, cy),
+                (is_solid(Some(col), row.checked_sub(1)), cx, y),
+                (is_solid(Some(col), Some(row + 1)), cx, y + 1.0),
+            ];
+            let connected = arms.iter().any(|(solid, _, _)| *solid);
+            arms.iter()
+                .filter(|(solid, _, _)| *solid || !connected)
+                .map(|&(_, ex, ey)| segment(cx, cy, ex, ey))
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
 
 #[derive(PartialEq)]
 pub enum GameState {
     Menu,
+    #[allow(dead_code)] // solo lo usa el bucle de la ventana (app.rs)
     Playing,
+    LevelComplete,
+    GameOver,
     Exiting,
 }
 
-pub fn load_maze_with_sprites(filename: &str, block_size: usize) -> (Maze, Vec<Sprite>) {
-    let file = File::open(filename).unwrap();
-    let reader = BufReader::new(file);
+/// Resultados del jugador en el nivel actual
+#[derive(Clone, Default)]
+pub struct LevelStats {
+    pub elapsed: f32,       // segundos jugados
+    pub pickups: usize,     // objetos recogidos
+    pub damage_taken: i32,
+    pub score: u32,         // puntos de los objetos recogidos (los que muestra el HUD)
+}
+
+/// Celdas que no bloquean el paso ni los rayos ('X' es la salida del nivel)
+pub fn is_open_cell(cell: char) -> bool {
+    matches!(cell, ' ' | 'X')
+}
 
-    let mut maze: Maze = Vec::new();
+/// Paredes que solo ocupan sus tramos dentro de la celda (ver
+/// `Maze::wall_segments`); el resto de la celda se puede pisar y ver
+pub fn is_thin_wall(cell: char) -> bool {
+    matches!(cell, '-' | '|' | '+' | '/' | '\\')
+}
+
+/// Nivel cargado desde archivo: el grid más toda la configuración que
+/// declara su cabecera de directivas `@`
+pub struct Level {
+    pub name: String,
+    pub maze: Maze,
+    pub sprites: Vec<Sprite>,
+    pub spawn: Vector2,                 // coordenadas del mundo
+    pub facing: f32,                    // ángulo inicial en radianes
+    pub wall_texture: Option<String>,
+    pub floor_texture: Option<String>,
+    pub ceiling_texture: Option<String>,
+    pub music: Option<String>,
+    pub surface_textures: Vec<String>,  // texturas de @floor-area y @ceiling-area
+    pub grid_line: usize,               // línea del archivo donde empieza el grid
+}
+
+/// Error al cargar un nivel. `line` y `column` empiezan en 1; valen 0
+/// cuando el error no corresponde a una posición del archivo.
+#[derive(Clone, Debug)]
+pub struct LevelError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl LevelError {
+    pub fn at(line: usize, column: usize, message: impl Into<String>) -> Self {
+        LevelError { line, column, message: message.into() }
+    }
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "línea {}, columna {}: {}", self.line, self.column, self.message)
+        }
+    }
+}
+
+/// Qué cambia una directiva de zona en sus celdas
+#[derive(Clone, Copy, PartialEq)]
+enum AreaKind {
+    Floor,       // `@floor-area`: textura de suelo
+    Ceiling,     // `@ceiling-area`: textura de techo
+    WallHeight,  // `@wall-height`: alto de las paredes
+    Pit,         // `@pit`: las celdas libres pasan a ser fosos
+}
+
+/// Zona rectangular de una directiva de zona (esquinas incluidas). `value`
+/// es lo que va después de las coordenadas: una textura o una altura.
+struct Area {
+    kind: AreaKind,
+    from: (usize, usize),
+    to: (usize, usize),
+    value: String,
+    line: usize,
+}
+
+impl Area {
+    fn parse(args: &str, kind: AreaKind, line: usize) -> Option<Area> {
+        let mut parts = args.splitn(5, ' ').map(str::trim);
+        let mut number = || parts.next()?.parse::<usize>().ok();
+        let from = (number()?, number()?);
+        let to = (number()?, number()?);
+        let value = parts.next().filter(|p| !p.is_empty())?.to_string();
+        if matches!(kind, AreaKind::WallHeight | AreaKind::Pit) && !value.parse::<f32>().is_ok_and(|h| h > 0.0) {
+            return None;
+        }
+        Some(Area { kind, from, to, value, line })
+    }
+}
+
+pub fn load_maze_with_sprites(filename: &str, block_size: usize) -> Result<Level, LevelError> {
+    parse_level(&read_level_source(filename)?, block_size)
+}
+
+/// Texto de un archivo de nivel, sin interpretar
+pub fn read_level_source(filename: &str) -> Result<String, LevelError> {
+    fs::read_to_string(filename)
+        .map_err(|e| LevelError::at(0, 0, format!("no se pudo leer '{}': {}", filename, e)))
+}
+
+/// Interpretar un nivel. Antes del grid puede llevar directivas:
+///
+/// ```text
+/// @name Nivel 1
+/// @spawn <columna> <fila> [grados]
+/// @wall ./textures/wall1.png
+/// @floor ./textures/floor1.png
+/// @ceiling ./textures/sky.png
+/// @floor-area <col1> <fila1> <col2> <fila2> ./textures/floor2.jpg
+/// @ceiling-area <col1> <fila1> <col2> <fila2> ./textures/wall1.png
+/// @wall-height <col1> <fila1> <col2> <fila2> <alto en bloques>
+/// @pit <col1> <fila1> <col2> <fila2> <profundidad en bloques>
+/// @music ./audio/music/song1.ogg
+/// @sprite <carácter> <tipo>
+/// @key <carácter llave> <carácter puerta> <nombre> <r>,<g>,<b>
+/// ```
+pub fn parse_level(source: &str, block_size: usize) -> Result<Level, LevelError> {
+    let mut name = String::from("Sin nombre");
+    let mut spawn = (1, 1, 0);  // (columna, fila, línea de la directiva)
+    let mut facing = 0.0;
+    let mut wall_texture = None;
+    let mut floor_texture = None;
+    let mut ceiling_texture = None;
+    let mut music = None;
+    let mut areas: Vec<Area> = Vec::new();
+    let mut legend: Vec<(char, &'static SpriteKind)> =
+        SPRITE_KINDS.iter().map(|k| (k.symbol, k)).collect();
+
+    let mut maze: Vec<Vec<char>> = Vec::new();
     let mut sprites: Vec<Sprite> = Vec::new();
+    let mut keys: Vec<KeyDef> = Vec::new();
+    let mut locks: Vec<(usize, usize, char)> = Vec::new();
+    let mut grid_line = 0;
 
-    for (row_idx, line) in reader.lines().enumerate() {
-        let mut row: Vec<char> = Vec::new();
-        for (col_idx, ch) in line.unwrap().chars().enumerate() {
-            match ch {
-                'O' => {
-                    let world_x = col_idx as f32 + 0.5;
-                    let world_y = row_idx as f32 + 0.5;
-                    sprites.push(Sprite::new_static(
-                        Vector2::new(world_x * block_size as f32,
-                                     world_y * block_size as f32),
-                        "key".to_string(),
-                        8.0,
-                        false,
-                    ));
-                    row.push(' ');
+    for (index, line) in source.lines().enumerate() {
+        let line_no = index + 1;
+
+        // Directivas del nivel antes del grid
+        if let Some(directive) = line.strip_prefix('@') {
+            if !maze.is_empty() {
+                return Err(LevelError::at(line_no, 1, "las directivas deben ir antes del grid"));
+            }
+            let (command, args) = directive.split_once(' ').unwrap_or((directive, ""));
+            let args = args.trim();
+            let args_col = command.len() + 3;
+            if args.is_empty() {
+                return Err(LevelError::at(line_no, args_col, format!("falta el valor de '@{}'", command)));
+            }
+
+            match command {
+                "name" => name = args.to_string(),
+                "spawn" => {
+                    let parts: Vec<&str> = args.split_whitespace().collect();
+                    let col = parts.first().and_then(|p| p.parse::<usize>().ok());
+                    let row = parts.get(1).and_then(|p| p.parse::<usize>().ok());
+                    let degrees = parts.get(2).map_or(Some(0.0), |p| p.parse::<f32>().ok());
+                    let (Some(col), Some(row), Some(degrees)) = (col, row, degrees) else {
+                        return Err(LevelError::at(line_no, args_col,
+                            "se esperaba `@spawn <columna> <fila> [grados]`"));
+                    };
+                    spawn = (col, row, line_no);
+                    facing = f32::to_radians(degrees);
                 }
-                'A' => {
-                    let world_x = col_idx as f32 + 0.5;
-                    let world_y = row_idx as f32 + 0.5;
-                    sprites.push(Sprite::new_static(
-                        Vector2::new(world_x * block_size as f32,
-                                    world_y * block_size as f32),
-                        "spike".to_string(),
-                        12.0,
-                        true
-                    ));
-                    row.push(' ');
+                "wall" => wall_texture = Some(args.to_string()),
+                "floor" => floor_texture = Some(args.to_string()),
+                "ceiling" => ceiling_texture = Some(args.to_string()),
+                "floor-area" | "ceiling-area" | "wall-height" | "pit" => {
+                    let (kind, value) = match command {
+                        "floor-area" => (AreaKind::Floor, "<textura>"),
+                        "ceiling-area" => (AreaKind::Ceiling, "<textura>"),
+                        "wall-height" => (AreaKind::WallHeight, "<alto>"),
+                        _ => (AreaKind::Pit, "<profundidad>"),
+                    };
+                    match Area::parse(args, kind, line_no) {
+                        Some(area) => areas.push(area),
+                        None => return Err(LevelError::at(line_no, args_col,
+                            format!("se esperaba `@{} <col1> <fila1> <col2> <fila2> {}`", command, value))),
+                    }
                 }
-                'F' => {
-                    let world_x = col_idx as f32 + 0.5;
-                    let world_y = row_idx as f32 + 0.5;
-                    sprites.push(Sprite::new_animated(
-                        Vector2::new(world_x * block_size as f32,
-                                     world_y * block_size as f32),
-                        vec!["fire1".to_string(), "fire2".to_string(), "fire3".to_string()],
-                        0.2,
-                        12.0,
-                        true,
-                    ));
-                    row.push(' ');
+                "music" => music = Some(args.to_string()),
+                "sprite" => {
+                    let mut parts = args.split_whitespace();
+                    let symbol = parts.next().and_then(|p| p.chars().next());
+                    let kind_name = parts.next().unwrap_or("");
+                    let Some(symbol) = symbol else {
+                        return Err(LevelError::at(line_no, args_col,
+                            "se esperaba `@sprite <carácter> <tipo>`"));
+                    };
+                    let Some(kind) = sprite_kind_by_name(kind_name) else {
+                        let known: Vec<&str> = SPRITE_KINDS.iter().map(|k| k.name).collect();
+                        return Err(LevelError::at(line_no, args_col + 2,
+                            format!("tipo de sprite desconocido '{}' (tipos: {})", kind_name, known.join(", "))));
+                    };
+                    legend.retain(|(c, _)| *c != symbol);
+                    legend.push((symbol, kind));
                 }
+                "key" => match KeyDef::parse(args) {
+                    Some(key) => keys.push(key),
+                    None => return Err(LevelError::at(line_no, args_col,
+                        "se esperaba `@key <llave> <puerta> <nombre> <r>,<g>,<b>`")),
+                },
+                _ => return Err(LevelError::at(line_no, 2, format!("directiva desconocida '@{}'", command))),
+            }
+            continue;
+        }
 
-                'H' => {
-                    let world_x = col_idx as f32 + 0.5;
-                    let world_y = row_idx as f32 + 0.5;
-                    sprites.push(Sprite::new_animated(
-                        Vector2::new(world_x * block_size as f32,
-                                     world_y * block_size as f32),
-                        vec!["heal1".to_string(), "heal2".to_string(), "heal3".to_string(), "heal4".to_string()],
-                        0.2,
-                        12.0,
-                        true,
-                    ));
-                    row.push(' ');
+        // Líneas en blanco antes del grid
+        if maze.is_empty() && line.trim().is_empty() {
+            continue;
+        }
+
+        if maze.is_empty() {
+            grid_line = line_no;
+        }
+        let row_idx = maze.len();
+        let mut row: Vec<char> = Vec::new();
+        for (col_idx, ch) in line.chars().enumerate() {
+            if let Some(key) = keys.iter().find(|k| k.door == ch) {
+                locks.push((col_idx, row_idx, key.id));
+                row.push('D');
+                continue;
+            }
+
+            // Las llaves declaradas usan el tipo de sprite de llave
+            let key = keys.iter().find(|k| k.id == ch);
+            let kind = match key {
+                Some(_) => sprite_kind(KEY_SYMBOL),
+                None => legend.iter().find(|(c, _)| *c == ch).map(|(_, k)| *k),
+            };
+            let Some(kind) = kind else {
+                row.push(ch);
+                continue;
+            };
+
+            let world_x = col_idx as f32 + 0.5;
+            let world_y = row_idx as f32 + 0.5;
+            let mut sprite = Sprite::from_kind(
+                kind,
+                Vector2::new(world_x * block_size as f32,
+                             world_y * block_size as f32),
+            );
+            if let (Some(key), Some(pickup)) = (key, sprite.pickup.as_mut()) {
+                pickup.key = Some(key.id);
+                pickup.message = format!("Llave {} obtenida", key.name);
+                sprite.tint = Some(key.color);
+            }
+            sprites.push(sprite);
+            row.push(' ');
+        }
+        maze.push(row);
+    }
+
+    if maze.is_empty() {
+        return Err(LevelError::at(0, 0, "el nivel no tiene grid"));
+    }
+
+    let mut maze = Maze::new(maze);
+    for (col, row, key) in locks {
+        if let Some(door) = maze.doors.iter_mut().find(|d| d.col == col && d.row == row) {
+            door.lock = Some(key);
+        }
+    }
+    maze.keys = keys;
+
+    // Texturas y alturas por zonas, en el orden en que se declaran
+    let mut surface_textures: Vec<String> = Vec::new();
+    for area in areas.iter() {
+        let (c0, c1) = (area.from.0.min(area.to.0), area.from.0.max(area.to.0));
+        let (r0, r1) = (area.from.1.min(area.to.1), area.from.1.max(area.to.1));
+        if r1 >= maze.len() || c1 >= maze[r1].len() {
+            return Err(LevelError::at(area.line, 1,
+                format!("la zona ({}, {})-({}, {}) se sale del grid", c0, r0, c1, r1)));
+        }
+
+        if matches!(area.kind, AreaKind::WallHeight | AreaKind::Pit) {
+            // Las paredes toman el alto y las celdas libres se hunden; las
+            // puertas siempre miden un bloque
+            let height: f32 = area.value.parse().unwrap_or(1.0);
+            for row in r0..=r1 {
+                for col in c0..=c1 {
+                    let cell = maze.cells[row][col];
+                    match area.kind {
+                        AreaKind::WallHeight if !is_open_cell(cell) && cell != 'D' => maze.heights[row][col] = height,
+                        AreaKind::Pit if is_open_cell(cell) => maze.heights[row][col] = -height,
+                        _ => {}
+                    }
                 }
-                _ => {
-                    row.push(ch);
+            }
+            continue;
+        }
+
+        let index = match surface_textures.iter().position(|p| *p == area.value) {
+            Some(index) => index,
+            None => {
+                surface_textures.push(area.value.clone());
+                surface_textures.len() - 1
+            }
+        };
+        for row in r0..=r1 {
+            for col in c0..=c1 {
+                let surface = &mut maze.surfaces[row][col];
+                if area.kind == AreaKind::Floor {
+                    surface.floor = Some(index);
+                } else {
+                    surface.ceiling = Some(index);
                 }
             }
         }
-        maze.push(row);
     }
 
-    (maze, sprites)
-}
+    let (spawn_col, spawn_row, spawn_line) = spawn;
+    if !maze.is_walkable(spawn_col, spawn_row) {
+        return Err(LevelError::at(spawn_line, 8,
+            format!("el punto de aparición ({}, {}) no es una celda libre", spawn_col, spawn_row)));
+    }
 
+    Ok(Level {
+        name,
+        maze,
+        sprites,
+        spawn: Vector2::new(
+            (spawn_col as f32 + 0.5) * block_size as f32,
+            (spawn_row as f32 + 0.5) * block_size as f32,
+        ),
+        facing,
+        wall_texture,
+        floor_texture,
+        ceiling_texture,
+        music,
+        surface_textures,
+        grid_line,
+    })
+}
 
+/// Dibujar el mapa en 2D con celdas de `block_size` píxeles a partir de
+/// `pos`. Los rayos se lanzan en el mundo, con celdas de `world_block_size`.
 pub fn render_maze(
     framebuffer: &mut Framebuffer,
     maze: &Maze,
     player: &Player,
     block_size: usize,
+    world_block_size: usize,
     pos: Vector2,
     with_rays: bool,
 ){
     // Pre-calcular colores como u32 para mejor rendimiento
     let black_color = rgba_to_u32(0, 0, 0, 255);
     let red_color = rgba_to_u32(255, 0, 0, 255);
+    let door_color = rgba_to_u32(200, 140, 40, 255);
+    let exit_color = rgba_to_u32(40, 220, 90, 255);
 
     for (row_index, row) in maze.iter().enumerate(){
         for (col_index, &cell) in row.iter().enumerate(){
             let xo = (col_index * block_size) + pos.x as usize;
             let yo = (row_index * block_size) + pos.y as usize;
-            draw_cell_optimized(framebuffer, xo, yo, block_size, cell, black_color, red_color);
+            // Las puertas abiertas se dibujan como pasillo y las cerradas
+            // con llave del color de su llave
+            let lock_color = maze
+                .door_at(col_index, row_index)
+                .and_then(|d| d.lock)
+                .and_then(|id| maze.key_def(id))
+                .map(|k| k.color);
+            let color = match cell {
+                'D' if maze.is_walkable(col_index, row_index) => black_color,
+                'D' => lock_color.unwrap_or(door_color),
+                'X' => exit_color,
+                ' ' => black_color,
+                c if is_thin_wall(c) => black_color,
+                _ => red_color,
+            };
+            draw_cell_optimized(framebuffer, xo, yo, block_size, color);
+        }
+    }
+
+    // Las paredes delgadas y diagonales se dibujan como líneas
+    let cell_size = block_size as f32;
+    for row_index in 0..maze.len() {
+        for col_index in 0..maze[row_index].len() {
+            for segment in maze.wall_segments(col_index, row_index) {
+                let from = Vector2::new(segment.from.x * cell_size + pos.x, segment.from.y * cell_size + pos.y);
+                let to = Vector2::new(segment.to.x * cell_size + pos.x, segment.to.y * cell_size + pos.y);
+                framebuffer.draw_line_fast(from, to, red_color);
+            }
         }
     }
 
+    // Las posiciones del jugador están en unidades del mundo
+    let scale = block_size as f32 / world_block_size as f32;
+
     if with_rays {
         let num_rays = 25;
         for i in 0..num_rays {
             let current_ray = i as f32 / num_rays as f32;
             let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
-            cast_ray(framebuffer, maze, player, block_size, a, pos, true);
+            let intersection = cast_ray(maze, player.pos, a, world_block_size);
+            draw_ray(framebuffer, player.pos, &intersection, pos, scale);
         }
     }
 
-    let scale = block_size as f32 / 30.0;
     let scaled_pos = Vector2::new(player.pos.x * scale, player.pos.y * scale);
     let triangle_size = block_size as f32 * 0.5;
 
-    let fake_player = Player {
-        pos: scaled_pos,
-        a: player.a,
-        fov: player.fov,
-    };
+    let mut fake_player = Player::new(scaled_pos);
+    fake_player.a = player.a;
+    fake_player.fov = player.fov;
 
     fake_player.draw_player_triangle(framebuffer, pos, triangle_size, Color::WHITESMOKE);
 }
@@ -138,15 +686,8 @@ fn draw_cell_optimized(
     xo: usize,
     yo: usize,
     block_size: usize,
-    cell: char,
-    black_color: u32,
-    red_color: u32
+    color: u32
 ){
-    let color = match cell {
-        ' ' => black_color,
-        _ => red_color,
-    };
-
     // Optimización: dibujar rectángulo sólido usando loops optimizados
     let end_x = xo + block_size;
     let end_y = yo + block_size;
@@ -0,0 +1,343 @@
+use std::collections::VecDeque;
+use std::fs;
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::{Rng, SeedableRng};
+
+use crate::validate::validate_source;
+
+/// Recursos disponibles para los niveles generados
+const WALL_TEXTURES: [&str; 3] = ["./textures/wall1.png", "./textures/wall2.png", "./textures/wall3.png"];
+const FLOOR_TEXTURES: [&str; 3] = ["./textures/floor1.png", "./textures/floor2.jpg", "./textures/floor3.png"];
+const MUSICS: [&str; 3] = ["./audio/music/song1.ogg", "./audio/music/song1.mp3", "./audio/music/song3.wav"];
+
+/// Parámetros del generador de laberintos
+#[derive(Clone, Copy, Debug)]
+pub struct GeneratorConfig {
+    pub width: usize,   // habitaciones a lo ancho
+    pub height: usize,  // habitaciones a lo alto
+    pub seed: u64,
+}
+
+impl GeneratorConfig {
+    /// Configuración del modo infinito: el laberinto crece con cada nivel
+    /// superado hasta el tamaño de los niveles hechos a mano (que cabe en
+    /// el minimapa)
+    pub fn endless(depth: usize, seed: u64) -> Self {
+        GeneratorConfig {
+            width: (6 + depth).min(10),
+            height: (6 + depth).min(10),
+            seed,
+        }
+    }
+}
+
+/// Pasillos abiertos entre habitaciones vecinas. `east[i]` une la
+/// habitación `i` con `i + 1` y `south[i]` con `i + width`.
+struct Passages {
+    width: usize,
+    height: usize,
+    east: Vec<bool>,
+    south: Vec<bool>,
+}
+
+impl Passages {
+    fn new(width: usize, height: usize) -> Self {
+        Passages {
+            width,
+            height,
+            east: vec![false; width * height],
+            south: vec![false; width * height],
+        }
+    }
+
+    /// Slot del pasillo entre dos habitaciones vecinas: (índice, es_este)
+    fn slot(&self, a: usize, b: usize) -> (usize, bool) {
+        let (low, high) = (a.min(b), a.max(b));
+        (low, high == low + 1)
+    }
+
+    fn open(&mut self, a: usize, b: usize) {
+        match self.slot(a, b) {
+            (i, true) => self.east[i] = true,
+            (i, false) => self.south[i] = true,
+        }
+    }
+
+    fn neighbors(&self, room: usize) -> Vec<usize> {
+        let (x, y) = (room % self.width, room / self.width);
+        let mut result = Vec::with_capacity(4);
+        if x > 0 {
+            result.push(room - 1);
+        }
+        if x + 1 < self.width {
+            result.push(room + 1);
+        }
+        if y > 0 {
+            result.push(room - self.width);
+        }
+        if y + 1 < self.height {
+            result.push(room + self.width);
+        }
+        result
+    }
+
+    fn is_open(&self, a: usize, b: usize) -> bool {
+        match self.slot(a, b) {
+            (i, true) => self.east[i],
+            (i, false) => self.south[i],
+        }
+    }
+
+    /// Distancia en habitaciones desde `start` y habitación anterior en el
+    /// camino. `blocked` es un pasillo que no se puede cruzar.
+    fn distances(&self, start: usize, blocked: Option<(usize, usize)>) -> (Vec<usize>, Vec<usize>) {
+        let count = self.width * self.height;
+        let mut distance = vec![usize::MAX; count];
+        let mut parent = vec![start; count];
+        let mut queue = VecDeque::new();
+        distance[start] = 0;
+        queue.push_back(start);
+
+        while let Some(room) = queue.pop_front() {
+            for next in self.neighbors(room) {
+                let crosses_blocked = blocked.is_some_and(|(a, b)| self.slot(a, b) == self.slot(room, next));
+                if !self.is_open(room, next) || crosses_blocked || distance[next] != usize::MAX {
+                    continue;
+                }
+                distance[next] = distance[room] + 1;
+                parent[next] = room;
+                queue.push_back(next);
+            }
+        }
+        (distance, parent)
+    }
+}
+
+/// Generar un nivel con backtracking recursivo. Devuelve el texto en el
+/// mismo formato que los archivos de `levels/`, con la llave siempre del
+/// lado de la aparición y la salida detrás de su cerradura.
+pub fn generate_level(config: &GeneratorConfig) -> String {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let width = config.width.max(2);
+    let height = config.height.max(2);
+    let room_count = width * height;
+
+    // === LABERINTO ===
+    let mut passages = Passages::new(width, height);
+    let mut visited = vec![false; room_count];
+    let mut stack = vec![0];
+    visited[0] = true;
+    while let Some(&current) = stack.last() {
+        let options: Vec<usize> = passages
+            .neighbors(current)
+            .into_iter()
+            .filter(|&n| !visited[n])
+            .collect();
+        let Some(&next) = options.choose(&mut rng) else {
+            stack.pop();
+            continue;
+        };
+        passages.open(current, next);
+        visited[next] = true;
+        stack.push(next);
+    }
+
+    // === SALIDA, CERRADURA Y LLAVE ===
+    // La salida va en la habitación más lejana a la aparición
+    let (distance, parent) = passages.distances(0, None);
+    let exit = (0..room_count).max_by_key(|&r| distance[r]).unwrap_or(0);
+    let mut path = vec![exit];
+    while let Some(&room) = path.last() {
+        if room == 0 {
+            break;
+        }
+        path.push(parent[room]);
+    }
+    path.reverse();
+
+    // Cerradura en el último tercio del camino; la llave en la habitación
+    // más lejana a la que se llega sin cruzarla
+    let lock_step = (path.len() * 2 / 3).clamp(1, path.len() - 1);
+    let locked = (path[lock_step - 1], path[lock_step]);
+    let (key_distance, _) = passages.distances(0, Some(locked));
+    let key_room = (0..room_count)
+        .filter(|&r| key_distance[r] != usize::MAX)
+        .max_by_key(|&r| key_distance[r])
+        .unwrap_or(0);
+
+    // Habitaciones del camino a la llave y a la salida: sin peligros
+    let mut critical = vec![false; room_count];
+    for &target in [exit, key_room].iter() {
+        let mut room = target;
+        critical[room] = true;
+        while room != 0 {
+            room = parent[room];
+            critical[room] = true;
+        }
+    }
+
+    // === TEXTO DEL GRID ===
+    let rows = height * 2 + 1;
+    let cols = width * 3 + 1;
+    let mut grid: Vec<Vec<char>> = (0..rows)
+        .map(|r| {
+            (0..cols)
+                .map(|c| match (r % 2 == 0, c % 3 == 0) {
+                    (true, true) => '+',
+                    (true, false) => '-',
+                    (false, true) => '|',
+                    (false, false) => ' ',
+                })
+                .collect()
+        })
+        .collect();
+
+    // Pasillos: (fila, columnas) de los caracteres que separan dos habitaciones
+    let slot_cells = |a: usize, b: usize| -> Vec<(usize, usize)> {
+        let low = a.min(b);
+        let (x, y) = (low % width, low / width);
+        if a.max(b) == low + 1 {
+            vec![(y * 2 + 1, x * 3 + 3)]
+        } else {
+            vec![(y * 2 + 2, x * 3 + 1), (y * 2 + 2, x * 3 + 2)]
+        }
+    };
+    let mut open_slots = Vec::new();
+    for room in 0..room_count {
+        for next in passages.neighbors(room) {
+            if next > room && passages.is_open(room, next) {
+                open_slots.push((room, next));
+                for (r, c) in slot_cells(room, next) {
+                    grid[r][c] = ' ';
+                }
+            }
+        }
+    }
+
+    for (r, c) in slot_cells(locked.0, locked.1) {
+        grid[r][c] = 'L';
+    }
+
+    // Algunas puertas normales en otros pasillos
+    open_slots.retain(|&slot| slot != (locked.0.min(locked.1), locked.0.max(locked.1)));
+    open_slots.shuffle(&mut rng);
+    for &(a, b) in open_slots.iter().take(room_count / 15) {
+        for (r, c) in slot_cells(a, b) {
+            grid[r][c] = 'D';
+        }
+    }
+
+    // Carácter interior de una habitación (cada una tiene dos)
+    let room_cell = |room: usize, right: bool| (room / width * 2 + 1, room % width * 3 + if right { 2 } else { 1 });
+
+    let (r, c) = room_cell(exit, false);
+    grid[r][c] = 'X';
+    let (r, c) = room_cell(key_room, true);
+    grid[r][c] = 'O';
+
+    // === PELIGROS Y CURACIONES ===
+    let mut free_rooms: Vec<usize> = (1..room_count)
+        .filter(|&r| r != exit && r != key_room)
+        .collect();
+    free_rooms.shuffle(&mut rng);
+
+    let hazard_count = room_count / 6;
+    let heal_count = room_count / 12;
+    let mut hazards = 0;
+    let mut heals = 0;
+    for room in free_rooms {
+        let (r, c) = room_cell(room, rng.random_bool(0.5));
+        if hazards < hazard_count && !critical[room] {
+            grid[r][c] = if rng.random_bool(0.5) { 'A' } else { 'F' };
+            hazards += 1;
+        } else if heals < heal_count {
+            grid[r][c] = 'H';
+            heals += 1;
+        }
+    }
+
+    // === CABECERA ===
+    let mut text = String::new();
+    text.push_str(&format!("@name Laberinto {}\n", config.seed));
+    text.push_str("@spawn 1 1 0\n");
+    text.push_str(&format!("@wall {}\n", WALL_TEXTURES[rng.random_range(0..WALL_TEXTURES.len())]));
+    text.push_str(&format!("@floor {}\n", FLOOR_TEXTURES[rng.random_range(0..FLOOR_TEXTURES.len())]));
+    text.push_str(&format!("@music {}\n", MUSICS[rng.random_range(0..MUSICS.len())]));
+    text.push_str("@sprite A spike\n");
+    text.push_str("@sprite F fire\n");
+    text.push_str("@sprite H heal\n");
+    text.push_str("@key O L dorada 255,215,0\n");
+    for row in grid {
+        text.extend(row);
+        text.push('\n');
+    }
+    text
+}
+
+/// Subcomando `generate-level [--seed N] [--width W] [--height H] [--depth D] [salida]`.
+/// `--depth` usa el tamaño del nivel D del modo infinito. Sin archivo de
+/// salida imprime el nivel. Devuelve el código de salida.
+pub fn run(args: &[String], block_size: usize) -> i32 {
+    let mut config = GeneratorConfig { width: 10, height: 10, seed: rand::random() };
+    let mut output: Option<&str> = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let target = match arg.as_str() {
+            "--seed" | "--width" | "--height" | "--depth" => arg.as_str(),
+            other if other.starts_with("--") => {
+                println!("Opción desconocida '{}'", other);
+                return 2;
+            }
+            path => {
+                if let Some(previous) = output {
+                    println!("Sobra el argumento '{}' (la salida ya es '{}')", path, previous);
+                    return 2;
+                }
+                output = Some(path);
+                continue;
+            }
+        };
+        let Some(value) = iter.next().and_then(|v| v.parse::<u64>().ok()) else {
+            println!("Se esperaba un número después de '{}'", target);
+            return 2;
+        };
+        match target {
+            "--seed" => config.seed = value,
+            "--width" => config.width = value as usize,
+            "--height" => config.height = value as usize,
+            _ => {
+                let endless = GeneratorConfig::endless(value as usize, config.seed);
+                config.width = endless.width;
+                config.height = endless.height;
+            }
+        }
+    }
+
+    let source = generate_level(&config);
+    let errors = validate_source(&source, block_size);
+    if !errors.is_empty() {
+        for error in errors.iter() {
+            println!("error: {}", error);
+        }
+        return 1;
+    }
+
+    match output {
+        Some(path) => match fs::write(path, &source) {
+            Ok(_) => {
+                println!("Nivel generado con semilla {} en '{}'", config.seed, path);
+                0
+            }
+            Err(e) => {
+                println!("No se pudo escribir '{}': {}", path, e);
+                1
+            }
+        },
+        None => {
+            print!("{}", source);
+            0
+        }
+    }
+}
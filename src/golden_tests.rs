@@ -0,0 +1,204 @@
+//! Pruebas de regresión con imágenes de referencia. Cada renderizador dibuja
+//! poses fijas de cámara en los niveles del juego y se compara contra los
+//! PNG de `tests/golden/`. Si algo cambia se guardan la imagen obtenida y
+//! una imagen de diferencias en `target/golden-diff/`.
+//!
+//! Para regenerar las referencias después de un cambio intencional:
+//! `UPDATE_GOLDEN=1 cargo test golden`
+
+use std::fs;
+use std::path::Path;
+
+use image::{Rgba, RgbaImage};
+
+use crate::framebuffer::Framebuffer;
+use crate::game::{load_maze_with_sprites, Maze};
+use crate::lights::LightMap;
+use crate::math::{Color, Vector2};
+use crate::play::load_level_textures;
+use crate::player::Player;
+use crate::renderer::{
+    render_world, render_world_with_textures, render_world_with_textures_downscale, render_world_with_textures_threaded,
+};
+use crate::snapshot::framebuffer_to_image;
+use crate::texture::TextureManager;
+use crate::LEVEL_FILES;
+
+const GOLDEN_DIR: &str = "tests/golden";
+const DIFF_DIR: &str = "target/golden-diff";
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 200;
+const BLOCK_SIZE: usize = 30;
+
+/// Diferencia máxima por canal para considerar dos píxeles iguales
+const CHANNEL_TOLERANCE: u8 = 8;
+/// Fracción de píxeles distintos que se acepta (redondeos entre máquinas)
+const MAX_MISMATCH_RATIO: f32 = 0.001;
+
+/// Poses de cámara: (nombre, columna, fila, grados, inclinación, agachado,
+/// altura de salto). Todas en celdas libres de los tres niveles.
+const POSES: [(&str, usize, usize, f32, f32, f32, f32); 8] = [
+    ("este", 1, 1, 0.0, 0.0, 0.0, 0.0),
+    ("sur", 1, 1, 90.0, 0.0, 0.0, 0.0),
+    ("diagonal", 1, 1, 45.0, 0.0, 0.0, 0.0),
+    ("oeste", 4, 1, 180.0, 0.0, 0.0, 0.0),
+    ("arriba", 1, 1, 0.0, 0.3, 0.0, 0.0),
+    ("abajo", 4, 1, 180.0, -0.3, 0.0, 0.0),
+    ("agachado", 1, 1, 0.0, 0.0, 1.0, 0.0),
+    ("salto", 4, 1, 180.0, 0.0, 0.0, 0.15),
+];
+
+#[derive(Clone, Copy)]
+enum Renderer {
+    Flat,
+    Textured,
+    Downscaled,
+}
+
+impl Renderer {
+    fn name(self) -> &'static str {
+        match self {
+            Renderer::Flat => "plano",
+            Renderer::Textured => "texturas",
+            Renderer::Downscaled => "reducido",
+        }
+    }
+
+    fn render(self, framebuffer: &mut Framebuffer, maze: &Maze, player: &Player, texture_manager: &TextureManager, lights: &LightMap) {
+        match self {
+            Renderer::Flat => render_world(framebuffer, maze, player, BLOCK_SIZE),
+            Renderer::Textured => render_world_with_textures(framebuffer, maze, player, BLOCK_SIZE, texture_manager, lights),
+            Renderer::Downscaled => {
+                render_world_with_textures_downscale(framebuffer, maze, player, BLOCK_SIZE, texture_manager, lights);
+            }
+        }
+    }
+}
+
+/// Resultado de comparar dos imágenes del mismo tamaño
+struct Comparison {
+    mismatched: usize,
+    diff: RgbaImage,
+}
+
+/// Comparar píxel a píxel. La imagen de diferencias muestra la referencia
+/// atenuada en gris y en rojo los píxeles fuera de tolerancia.
+fn compare(expected: &RgbaImage, actual: &RgbaImage) -> Comparison {
+    let mut mismatched = 0;
+    let diff = RgbaImage::from_fn(expected.width(), expected.height(), |x, y| {
+        let e = expected.get_pixel(x, y);
+        let a = actual.get_pixel(x, y);
+        let differs = e.0.iter().zip(a.0.iter()).any(|(e, a)| e.abs_diff(*a) > CHANNEL_TOLERANCE);
+        if differs {
+            mismatched += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let gray = ((e[0] as u32 + e[1] as u32 + e[2] as u32) / 9) as u8;
+            Rgba([gray, gray, gray, 255])
+        }
+    });
+    Comparison { mismatched, diff }
+}
+
+/// Dibujar todas las poses de todos los niveles con un renderizador y
+/// compararlas con su referencia. Falla listando cada imagen distinta.
+fn check_renderer(renderer: Renderer) {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut failures = Vec::new();
+
+    for level_path in LEVEL_FILES.iter() {
+        let level = load_maze_with_sprites(level_path, BLOCK_SIZE)
+            .unwrap_or_else(|e| panic!("{}: {}", level_path, e));
+        let mut texture_manager = TextureManager::new();
+        load_level_textures(&mut texture_manager, &level);
+        let lights = LightMap::from_sprites(&level.maze, &level.sprites, BLOCK_SIZE);
+        let level_name = Path::new(level_path).file_stem().unwrap().to_string_lossy();
+
+        for (pose_name, col, row, degrees, pitch, crouch, z) in POSES.iter() {
+            let pos = Vector2::new((*col as f32 + 0.5) * BLOCK_SIZE as f32, (*row as f32 + 0.5) * BLOCK_SIZE as f32);
+            let mut player = Player::new(pos);
+            player.a = degrees.to_radians();
+            player.pitch = *pitch;
+            player.crouch = *crouch;
+            player.z = *z;
+
+            let mut framebuffer = Framebuffer::new(WIDTH, HEIGHT, Color::BLACK);
+            renderer.render(&mut framebuffer, &level.maze, &player, &texture_manager, &lights);
+            let actual = framebuffer_to_image(&framebuffer);
+
+            let file_name = format!("{}_{}_{}.png", level_name, renderer.name(), pose_name);
+            let golden_path = Path::new(GOLDEN_DIR).join(&file_name);
+            if update {
+                fs::create_dir_all(GOLDEN_DIR).unwrap();
+                actual.save(&golden_path).unwrap();
+                continue;
+            }
+
+            let expected = match image::open(&golden_path) {
+                Ok(image) => image.to_rgba8(),
+                Err(e) => {
+                    failures.push(format!("{}: no se pudo abrir la referencia ({})", file_name, e));
+                    continue;
+                }
+            };
+            if expected.dimensions() != actual.dimensions() {
+                failures.push(format!("{}: tamaño {:?}, se esperaba {:?}", file_name, actual.dimensions(), expected.dimensions()));
+                continue;
+            }
+
+            let comparison = compare(&expected, &actual);
+            let allowed = (WIDTH * HEIGHT) as f32 * MAX_MISMATCH_RATIO;
+            if comparison.mismatched as f32 > allowed {
+                fs::create_dir_all(DIFF_DIR).unwrap();
+                let stem = file_name.trim_end_matches(".png");
+                actual.save(Path::new(DIFF_DIR).join(format!("{}_obtenida.png", stem))).unwrap();
+                comparison.diff.save(Path::new(DIFF_DIR).join(format!("{}_diff.png", stem))).unwrap();
+                failures.push(format!("{}: {} píxeles distintos (máximo {})", file_name, comparison.mismatched, allowed as usize));
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "imágenes distintas a la referencia (ver {}):\n{}",
+        DIFF_DIR,
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn golden_render_world() {
+    check_renderer(Renderer::Flat);
+}
+
+#[test]
+fn golden_render_world_with_textures() {
+    check_renderer(Renderer::Textured);
+}
+
+#[test]
+fn golden_render_world_with_textures_downscale() {
+    check_renderer(Renderer::Downscaled);
+}
+
+/// Repartir las columnas entre hilos no puede cambiar ni un píxel, con
+/// cualquier cantidad de franjas (incluidas las que no dividen el ancho)
+#[test]
+fn threaded_render_matches_single_thread() {
+    let level = load_maze_with_sprites(LEVEL_FILES[0], BLOCK_SIZE).unwrap();
+    let mut texture_manager = TextureManager::new();
+    load_level_textures(&mut texture_manager, &level);
+    let lights = LightMap::from_sprites(&level.maze, &level.sprites, BLOCK_SIZE);
+    let mut player = Player::new(level.spawn);
+    player.a = 0.3;
+
+    let mut single = Framebuffer::new(WIDTH + 1, HEIGHT, Color::BLACK);
+    let single_depth = render_world_with_textures_threaded(&mut single, &level.maze, &player, BLOCK_SIZE, &texture_manager, &lights, 1);
+    for threads in [2, 3, 7, 64] {
+        let mut threaded = Framebuffer::new(WIDTH + 1, HEIGHT, Color::BLACK);
+        let depth = render_world_with_textures_threaded(&mut threaded, &level.maze, &player, BLOCK_SIZE, &texture_manager, &lights, threads);
+        assert!(single.pixels() == threaded.pixels(), "{} hilos cambian la imagen", threads);
+        assert!(single_depth == depth, "{} hilos cambian el depth buffer", threads);
+    }
+}
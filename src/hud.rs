@@ -0,0 +1,172 @@
+use crate::framebuffer::{Framebuffer, rgba_to_u32};
+use crate::font::{draw_text, draw_text_outlined, text_width, GLYPH_HEIGHT};
+use crate::game::{LevelStats, Maze};
+use crate::player::Player;
+
+/// Mensajes temporales que se muestran en pantalla
+#[derive(Default)]
+pub struct Messages {
+    entries: Vec<(String, f32)>, // (texto, segundos restantes)
+}
+
+impl Messages {
+    /// Segundos que permanece visible cada mensaje
+    pub const DURATION: f32 = 2.5;
+
+    pub fn push(&mut self, text: &str) {
+        self.entries.push((text.to_string(), Self::DURATION));
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for entry in self.entries.iter_mut() {
+            entry.1 -= dt;
+        }
+        self.entries.retain(|(_, remaining)| *remaining > 0.0);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(text, _)| text.as_str())
+    }
+}
+
+/// Dibujar el HUD del juego directamente en el framebuffer: barra de salud,
+/// llaves, nombre del nivel, objetos recogidos, mira y mensajes temporales
+pub fn draw_hud(
+    framebuffer: &mut Framebuffer,
+    player: &Player,
+    maze: &Maze,
+    level_name: &str,
+    stats: &LevelStats,
+    messages: &Messages,
+) {
+    let screen_width = framebuffer.width as i32;
+    let screen_height = framebuffer.height as i32;
+
+    let white = rgba_to_u32(235, 235, 235, 255);
+    let black = rgba_to_u32(0, 0, 0, 255);
+    let red = rgba_to_u32(200, 30, 30, 255);
+
+    // Destello rojo mientras dura la invulnerabilidad tras un golpe
+    if player.invulnerable > 0.0 {
+        let intensity = player.invulnerable / Player::INVULNERABILITY_TIME;
+        framebuffer.blend_rect(0, 0, screen_width as u32, screen_height as u32, red, 0.3 * intensity);
+    }
+
+    draw_crosshair(framebuffer, player);
+
+    // === SALUD ===
+    let panel_y = screen_height - 50;
+    framebuffer.blend_rect(10, panel_y, 260, 40, black, 0.55);
+    draw_text(framebuffer, "SALUD", 20, panel_y + 13, 2, white);
+
+    let bar_x = 90;
+    let bar_y = panel_y + 10;
+    let bar_width = 170;
+    let bar_height = 20;
+    let ratio = (player.health as f32 / player.max_health as f32).clamp(0.0, 1.0);
+    framebuffer.fill_rect(bar_x - 1, bar_y - 1, bar_width + 2, bar_height + 2, white);
+    framebuffer.fill_rect(bar_x, bar_y, bar_width, bar_height, rgba_to_u32(60, 0, 0, 255));
+    framebuffer.fill_rect(bar_x, bar_y, (bar_width as f32 * ratio) as u32, bar_height, red);
+
+    let health_text = format!("{}/{}", player.health, player.max_health);
+    let health_x = bar_x + (bar_width as i32 - text_width(&health_text, 2) as i32) / 2;
+    draw_text_outlined(framebuffer, &health_text, health_x, bar_y + 3, 2, white, black);
+
+    // === LLAVES ===
+    for (i, &id) in player.keys.iter().enumerate() {
+        let color = maze
+            .key_def(id)
+            .map(|k| k.color)
+            .unwrap_or(rgba_to_u32(255, 215, 0, 255));
+        let key_x = 282 + i as i32 * 24;
+        framebuffer.fill_rect(key_x - 1, panel_y + 9, 20, 22, black);
+        framebuffer.fill_rect(key_x + 5, panel_y + 10, 8, 8, color);
+        framebuffer.fill_rect(key_x + 8, panel_y + 18, 2, 12, color);
+        framebuffer.fill_rect(key_x + 10, panel_y + 24, 4, 2, color);
+        framebuffer.fill_rect(key_x + 10, panel_y + 28, 3, 2, color);
+    }
+
+    // === NOMBRE DEL NIVEL ===
+    let name_width = text_width(level_name, 2) as i32;
+    let name_x = (screen_width - name_width) / 2;
+    framebuffer.blend_rect(name_x - 10, 10, name_width as u32 + 20, 30, black, 0.55);
+    draw_text(framebuffer, level_name, name_x, 18, 2, white);
+
+    // === OBJETOS Y PUNTOS ===
+    let items_text = format!("OBJETOS {}  PUNTOS {}", stats.pickups, stats.score);
+    let items_width = text_width(&items_text, 2) as i32;
+    framebuffer.blend_rect(screen_width - items_width - 30, panel_y, items_width as u32 + 20, 40, black, 0.55);
+    draw_text(framebuffer, &items_text, screen_width - items_width - 20, panel_y + 13, 2, white);
+
+    // === MENSAJES ===
+    let line_height = (GLYPH_HEIGHT * 2 + 6) as i32;
+    for (i, message) in messages.iter().enumerate() {
+        let x = (screen_width - text_width(message, 2) as i32) / 2;
+        let y = panel_y - 40 - i as i32 * line_height;
+        draw_text_outlined(framebuffer, message, x, y, 2, rgba_to_u32(255, 230, 120, 255), black);
+    }
+}
+
+/// Mira en el centro de la pantalla; se pone roja al recibir daño
+fn draw_crosshair(framebuffer: &mut Framebuffer, player: &Player) {
+    let center_x = framebuffer.width as i32 / 2;
+    let center_y = framebuffer.height as i32 / 2;
+    let color = if player.invulnerable > 0.0 {
+        rgba_to_u32(255, 60, 60, 255)
+    } else {
+        rgba_to_u32(220, 220, 220, 255)
+    };
+
+    let gap = 3;
+    let length = 7;
+    framebuffer.fill_rect(center_x - gap - length, center_y - 1, length as u32, 2, color);
+    framebuffer.fill_rect(center_x + gap, center_y - 1, length as u32, 2, color);
+    framebuffer.fill_rect(center_x - 1, center_y - gap - length, 2, length as u32, color);
+    framebuffer.fill_rect(center_x - 1, center_y + gap, 2, length as u32, color);
+}
+
+/// Oscurecer la escena y mostrar el aviso de pausa
+pub fn draw_pause(framebuffer: &mut Framebuffer) {
+    let width = framebuffer.width;
+    let height = framebuffer.height;
+    framebuffer.blend_rect(0, 0, width, height, rgba_to_u32(0, 0, 0, 255), 0.5);
+
+    let title = "PAUSA";
+    let x = (width as i32 - text_width(title, 6) as i32) / 2;
+    let y = height as i32 / 2 - (GLYPH_HEIGHT * 6) as i32;
+    draw_text_outlined(framebuffer, title, x, y, 6, rgba_to_u32(235, 235, 235, 255), rgba_to_u32(0, 0, 0, 255));
+
+    let hint = "TAB PARA CONTINUAR";
+    let x = (width as i32 - text_width(hint, 2) as i32) / 2;
+    draw_text(framebuffer, hint, x, height as i32 / 2 + 20, 2, rgba_to_u32(200, 200, 200, 255));
+}
+
+/// Información de depuración y ayuda de controles en la esquina superior
+/// izquierda
+pub fn draw_debug_text(framebuffer: &mut Framebuffer, lines: &[String], help: &str) {
+    let white = rgba_to_u32(255, 255, 255, 255);
+    let black = rgba_to_u32(0, 0, 0, 255);
+
+    let mut y = 10;
+    for line in lines.iter() {
+        draw_text_outlined(framebuffer, line, 10, y, 2, white, black);
+        y += (GLYPH_HEIGHT * 2 + 6) as i32;
+    }
+    draw_text_outlined(framebuffer, help, 10, y, 1, rgba_to_u32(200, 200, 200, 255), black);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{Color, Vector2};
+
+    #[test]
+    fn health_wider_than_the_bar_still_draws() {
+        let mut framebuffer = Framebuffer::new(320, 200, Color::BLACK);
+        let mut player = Player::new(Vector2::new(45.0, 45.0));
+        player.max_health = 1_000_000_000;
+        player.health = player.max_health;
+        let maze = Maze::new(vec![vec!['+'; 3]; 3]);
+        draw_hud(&mut framebuffer, &player, &maze, "Nivel", &LevelStats::default(), &Messages::default());
+    }
+}
@@ -0,0 +1,203 @@
+//! Luces puntuales que llevan algunos sprites (el fuego). Cada luz ilumina
+//! las paredes, el suelo y el techo que ve directamente: la visibilidad se
+//! calcula con rayos contra el maze y se guarda en un mapa de luz de
+//! varias muestras por celda que el renderer consulta por píxel.
+
+use crate::cast::cast_ray;
+use crate::game::Maze;
+use crate::math::Vector2;
+use crate::sprites::Sprite;
+
+/// Muestras del mapa de luz por lado de celda. Las paredes (en el borde o
+/// en el medio de la celda) caen siempre entre dos muestras.
+const SAMPLES_PER_CELL: usize = 4;
+
+/// Luz que emite un tipo de sprite
+#[derive(Clone, Copy, Debug)]
+pub struct LightDef {
+    pub color: u32,      // RGBA como u32
+    pub radius: f32,     // alcance en bloques
+    pub intensity: f32,  // brillo junto a la luz (1.0 = color de la textura sin oscurecer)
+    pub flicker: f32,    // cuánto baja el brillo en el frame más débil (0.0 a 1.0)
+}
+
+/// Luz ya ubicada en el mundo con el brillo del frame actual
+#[derive(Clone, Copy, Debug)]
+pub struct PointLight {
+    pub pos: Vector2,      // coordenadas del mundo
+    pub color: [f32; 3],   // RGB ya multiplicado por el brillo
+    pub radius: f32,       // unidades del mundo
+}
+
+/// Luces de los sprites que emiten. El parpadeo sigue a la animación: cada
+/// frame de textura tiene su propio brillo, así la luz cambia justo cuando
+/// cambia la llama.
+pub fn sprite_lights(sprites: &[Sprite], block_size: usize) -> Vec<PointLight> {
+    sprites
+        .iter()
+        .filter_map(|sprite| {
+            let def = sprite.light?;
+            let frame = sprite.animation.as_ref().map_or(0, |anim| anim.current_frame);
+            let brightness = def.intensity * (1.0 - def.flicker * flicker_level(frame));
+            let channel = |shift: u32| ((def.color >> shift) & 0xFF) as f32 / 255.0 * brightness;
+            Some(PointLight {
+                pos: sprite.pos,
+                color: [channel(0), channel(8), channel(16)],
+                radius: def.radius * block_size as f32,
+            })
+        })
+        .collect()
+}
+
+/// Caída del brillo en el frame `frame`, en [0, 1). Sale de la secuencia de
+/// la razón áurea para que frames seguidos queden bien distintos.
+fn flicker_level(frame: usize) -> f32 {
+    ((frame as f32 + 1.0) * 0.618_034).fract()
+}
+
+/// Luz acumulada de todas las luces en una grilla de muestras sobre el
+/// maze. La visibilidad de cada luz se guarda y solo se recalcula si se
+/// mueve la luz o una puerta cercana; en cada frame solo se suman los
+/// brillos.
+#[derive(Default)]
+pub struct LightMap {
+    width: usize,                     // muestras a lo ancho
+    height: usize,                    // muestras a lo alto
+    sample_size: f32,                 // unidades del mundo entre muestras
+    light: Vec<[f32; 3]>,
+    visible: Vec<Vec<(usize, f32)>>,  // por luz: (muestra, atenuación) de las muestras que ve
+    positions: Vec<Vector2>,          // posición de cada luz al calcular `visible`
+    doors: Vec<f32>,                  // apertura de cada puerta al calcular `visible`
+}
+
+impl LightMap {
+    pub fn new() -> Self {
+        LightMap::default()
+    }
+
+    /// Mapa con las luces de los sprites del nivel en su estado actual
+    pub fn from_sprites(maze: &Maze, sprites: &[Sprite], block_size: usize) -> Self {
+        let mut light_map = LightMap::new();
+        light_map.update(maze, &sprite_lights(sprites, block_size), block_size);
+        light_map
+    }
+
+    /// Rehacer la iluminación con los brillos de este frame
+    pub fn update(&mut self, maze: &Maze, lights: &[PointLight], block_size: usize) {
+        let width = maze.iter().map(|row| row.len()).max().unwrap_or(0) * SAMPLES_PER_CELL;
+        let height = maze.len() * SAMPLES_PER_CELL;
+        let moved = self.positions.len() != lights.len()
+            || self.positions.iter().zip(lights.iter()).any(|(pos, light)| *pos != light.pos);
+
+        if moved || width != self.width || height != self.height || self.doors.len() != maze.doors.len() {
+            self.width = width;
+            self.height = height;
+            self.sample_size = block_size as f32 / SAMPLES_PER_CELL as f32;
+            self.positions = lights.iter().map(|light| light.pos).collect();
+            self.visible = lights.iter().map(|light| self.visible_samples(maze, light, block_size)).collect();
+        } else {
+            // Solo las luces que alcanzan a una puerta que se movió
+            for (index, door) in maze.doors.iter().enumerate() {
+                if self.doors[index] == door.open_amount {
+                    continue;
+                }
+                let center = Vector2::new(
+                    (door.col as f32 + 0.5) * block_size as f32,
+                    (door.row as f32 + 0.5) * block_size as f32,
+                );
+                for (i, light) in lights.iter().enumerate() {
+                    if distance(light.pos, center) < light.radius + block_size as f32 {
+                        self.visible[i] = self.visible_samples(maze, light, block_size);
+                    }
+                }
+            }
+        }
+        self.doors = maze.doors.iter().map(|door| door.open_amount).collect();
+
+        self.light.clear();
+        self.light.resize(width * height, [0.0; 3]);
+        for (light, visible) in lights.iter().zip(self.visible.iter()) {
+            for &(sample, falloff) in visible.iter() {
+                for (total, color) in self.light[sample].iter_mut().zip(light.color.iter()) {
+                    *total += color * falloff;
+                }
+            }
+        }
+    }
+
+    /// Muestras dentro del radio que la luz ve sin paredes de por medio,
+    /// con la atenuación por distancia de cada una
+    fn visible_samples(&self, maze: &Maze, light: &PointLight, block_size: usize) -> Vec<(usize, f32)> {
+        let size = self.sample_size;
+        let first_x = ((light.pos.x - light.radius) / size).max(0.0) as usize;
+        let first_y = ((light.pos.y - light.radius) / size).max(0.0) as usize;
+        let last_x = (((light.pos.x + light.radius) / size) as usize).min(self.width.saturating_sub(1));
+        let last_y = (((light.pos.y + light.radius) / size) as usize).min(self.height.saturating_sub(1));
+
+        let mut visible = Vec::new();
+        for y in first_y..=last_y {
+            for x in first_x..=last_x {
+                let sample = Vector2::new((x as f32 + 0.5) * size, (y as f32 + 0.5) * size);
+                let d = distance(light.pos, sample);
+                if d >= light.radius {
+                    continue;
+                }
+                let angle = (sample.y - light.pos.y).atan2(sample.x - light.pos.x);
+                if cast_ray(maze, light.pos, angle, block_size).distance < d {
+                    continue;
+                }
+                let falloff = 1.0 - d / light.radius;
+                visible.push((y * self.width + x, falloff * falloff));
+            }
+        }
+        visible
+    }
+
+    /// Luz de la muestra que contiene el punto (x, y) del mundo. Para las
+    /// paredes: el punto, apenas delante de la cara, cae en una muestra del
+    /// lado que se ve.
+    #[inline]
+    pub fn at(&self, x: f32, y: f32) -> [f32; 3] {
+        if self.light.is_empty() || x < 0.0 || y < 0.0 {
+            return [0.0; 3];
+        }
+        let (sx, sy) = ((x / self.sample_size) as usize, (y / self.sample_size) as usize);
+        if sx >= self.width || sy >= self.height {
+            return [0.0; 3];
+        }
+        self.light[sy * self.width + sx]
+    }
+
+    /// Luz en (x, y) interpolada entre las cuatro muestras más cercanas,
+    /// para que el suelo y el techo no se vean en cuadros
+    #[inline]
+    pub fn at_smooth(&self, x: f32, y: f32) -> [f32; 3] {
+        if self.light.is_empty() {
+            return [0.0; 3];
+        }
+        let fx = (x / self.sample_size - 0.5).max(0.0);
+        let fy = (y / self.sample_size - 0.5).max(0.0);
+        let (x0, y0) = (fx as usize, fy as usize);
+        if x0 + 1 >= self.width || y0 + 1 >= self.height {
+            return self.at(x, y);
+        }
+        let (tx, ty) = (fx.fract(), fy.fract());
+        let sample = |sx: usize, sy: usize| self.light[sy * self.width + sx];
+        let (a, b, c, d) = (sample(x0, y0), sample(x0 + 1, y0), sample(x0, y0 + 1), sample(x0 + 1, y0 + 1));
+        // Lejos de las luces las cuatro muestras son iguales (casi siempre cero)
+        if a == b && a == c && a == d {
+            return a;
+        }
+        let mut result = [0.0; 3];
+        for channel in 0..3 {
+            let top = a[channel] + (b[channel] - a[channel]) * tx;
+            let bottom = c[channel] + (d[channel] - c[channel]) * tx;
+            result[channel] = top + (bottom - top) * ty;
+        }
+        result
+    }
+}
+
+fn distance(a: Vector2, b: Vector2) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
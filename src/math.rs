@@ -0,0 +1,126 @@
+//! Tipos básicos de matemática y color usados por el núcleo del juego, sin
+//! depender de ningún backend gráfico.
+
+/// Vector 2D en coordenadas del mundo o de pantalla
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vector2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vector2 {
+    pub const fn new(x: f32, y: f32) -> Self {
+        Vector2 { x, y }
+    }
+}
+
+/// Color RGBA de 8 bits por canal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::new(255, 255, 255, 255);
+    pub const BLACK: Color = Color::new(0, 0, 0, 255);
+    pub const WHITESMOKE: Color = Color::new(245, 245, 245, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
+    }
+}
+
+/// Tramo recto entre dos puntos (paredes delgadas, diagonales y hojas de
+/// puerta, en coordenadas de celda)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segment {
+    pub from: Vector2,
+    pub to: Vector2,
+}
+
+impl Segment {
+    pub const fn new(from: Vector2, to: Vector2) -> Self {
+        Segment { from, to }
+    }
+
+    /// Distancia del punto `p` al punto más cercano del tramo
+    pub fn distance_to(&self, p: Vector2) -> f32 {
+        let (ex, ey) = (self.to.x - self.from.x, self.to.y - self.from.y);
+        let length_sq = ex * ex + ey * ey;
+        let s = if length_sq == 0.0 {
+            0.0
+        } else {
+            (((p.x - self.from.x) * ex + (p.y - self.from.y) * ey) / length_sq).clamp(0.0, 1.0)
+        };
+        let (dx, dy) = (self.from.x + s * ex - p.x, self.from.y + s * ey - p.y);
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// Cruce con el rayo `origin + t * dir`: devuelve (t, s, lado), con `s`
+    /// la posición a lo largo del tramo en [0, 1] y `lado` positivo si el
+    /// rayo cruza el tramo de izquierda a derecha. `None` si no se cruzan
+    /// delante del origen o son paralelos.
+    pub fn intersect_ray(&self, origin: Vector2, dir: Vector2) -> Option<(f32, f32, f32)> {
+        let (ex, ey) = (self.to.x - self.from.x, self.to.y - self.from.y);
+        let cross = dir.x * ey - dir.y * ex;
+        if cross == 0.0 {
+            return None;
+        }
+        let (ax, ay) = (self.from.x - origin.x, self.from.y - origin.y);
+        let t = (ax * ey - ay * ex) / cross;
+        let s = (ax * dir.y - ay * dir.x) / cross;
+        if t < 0.0 || !(0.0..=1.0).contains(&s) {
+            return None;
+        }
+        Some((t, s, cross))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(x0: f32, y0: f32, x1: f32, y1: f32) -> Segment {
+        Segment::new(Vector2::new(x0, y0), Vector2::new(x1, y1))
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn ray_crosses_thin_wall_segments() {
+        // '-': de izquierda a derecha, golpeado desde abajo
+        let (t, s, cross) = segment(0.0, 0.5, 1.0, 0.5).intersect_ray(Vector2::new(0.25, 2.0), Vector2::new(0.0, -1.0)).unwrap();
+        assert!(close(t, 1.5) && close(s, 0.25) && cross > 0.0);
+
+        // '|': de arriba a abajo, golpeado desde la izquierda
+        let (t, s, cross) = segment(0.5, 0.0, 0.5, 1.0).intersect_ray(Vector2::new(-1.0, 0.75), Vector2::new(1.0, 0.0)).unwrap();
+        assert!(close(t, 1.5) && close(s, 0.75) && cross > 0.0);
+
+        // '/': golpeado desde la esquina superior izquierda, justo al medio
+        let dir = Vector2::new(0.5_f32.sqrt(), 0.5_f32.sqrt());
+        let (t, s, cross) = segment(0.0, 1.0, 1.0, 0.0).intersect_ray(Vector2::new(0.0, 0.0), dir).unwrap();
+        assert!(close(t, 0.5_f32.sqrt()) && close(s, 0.5) && cross < 0.0);
+    }
+
+    #[test]
+    fn ray_misses_parallel_and_behind_segments() {
+        let wall = segment(0.0, 0.5, 1.0, 0.5);
+        assert!(wall.intersect_ray(Vector2::new(-1.0, 0.5), Vector2::new(1.0, 0.0)).is_none());
+        assert!(wall.intersect_ray(Vector2::new(0.5, 0.0), Vector2::new(-1.0, 0.0)).is_none());
+        assert!(wall.intersect_ray(Vector2::new(0.5, 2.0), Vector2::new(0.0, 1.0)).is_none());
+        assert!(wall.intersect_ray(Vector2::new(1.5, 2.0), Vector2::new(0.0, -1.0)).is_none());
+    }
+
+    #[test]
+    fn distance_to_clamps_to_the_ends() {
+        let wall = segment(0.0, 0.5, 1.0, 0.5);
+        assert!(close(wall.distance_to(Vector2::new(0.3, 0.2)), 0.3));
+        assert!(close(wall.distance_to(Vector2::new(1.3, 0.9)), 0.5));
+        assert!(close(segment(0.5, 0.5, 0.5, 0.5).distance_to(Vector2::new(0.5, 0.0)), 0.5));
+    }
+}
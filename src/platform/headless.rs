@@ -0,0 +1,130 @@
+use std::collections::HashSet;
+
+use crate::framebuffer::Framebuffer;
+use crate::platform::{Key, Platform};
+
+/// Backend sin ventana ni audio para pruebas y herramientas. Cada frame
+/// dura exactamente `frame_time`, la entrada se controla desde el código
+/// y el último framebuffer presentado queda guardado.
+pub struct HeadlessPlatform {
+    pub width: i32,
+    pub height: i32,
+    pub frame_time: f32,
+    pub frames_presented: usize,
+    pub max_frames: Option<usize>,  // cerrar después de esta cantidad de frames
+    pub last_frame: Vec<u32>,       // píxeles RGBA del último frame presentado
+    pub music: Option<String>,      // última música pedida
+    pub effects: Vec<String>,       // efectos reproducidos, en orden
+    keys_down: HashSet<Key>,
+    keys_pressed: HashSet<Key>,
+    mouse_x: i32,
+    mouse_y: i32,
+}
+
+impl HeadlessPlatform {
+    pub fn new(width: i32, height: i32) -> Self {
+        HeadlessPlatform {
+            width,
+            height,
+            frame_time: 1.0 / 60.0,
+            frames_presented: 0,
+            max_frames: None,
+            last_frame: Vec::new(),
+            music: None,
+            effects: Vec::new(),
+            keys_down: HashSet::new(),
+            keys_pressed: HashSet::new(),
+            mouse_x: width / 2,
+            mouse_y: height / 2,
+        }
+    }
+}
+
+/// Control de la entrada desde las pruebas
+#[cfg(test)]
+impl HeadlessPlatform {
+    /// Mantener presionada una tecla hasta llamar a `release`
+    pub fn hold(&mut self, key: Key) {
+        if self.keys_down.insert(key) {
+            self.keys_pressed.insert(key);
+        }
+    }
+
+    pub fn release(&mut self, key: Key) {
+        self.keys_down.remove(&key);
+    }
+
+    /// Presionar una tecla solo durante el próximo frame
+    pub fn tap(&mut self, key: Key) {
+        self.keys_pressed.insert(key);
+    }
+
+    pub fn set_mouse_x(&mut self, x: i32) {
+        self.mouse_x = x;
+    }
+
+    pub fn set_mouse_y(&mut self, y: i32) {
+        self.mouse_y = y;
+    }
+}
+
+impl Platform for HeadlessPlatform {
+    fn should_close(&self) -> bool {
+        self.max_frames.is_some_and(|max| self.frames_presented >= max)
+    }
+
+    fn frame_time(&self) -> f32 {
+        self.frame_time
+    }
+
+    fn fps(&self) -> u32 {
+        (1.0 / self.frame_time).round() as u32
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    fn is_key_pressed(&self, key: Key) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    fn mouse_x(&self) -> i32 {
+        self.mouse_x
+    }
+
+    fn mouse_y(&self) -> i32 {
+        self.mouse_y
+    }
+
+    fn screen_width(&self) -> i32 {
+        self.width
+    }
+
+    fn screen_height(&self) -> i32 {
+        self.height
+    }
+
+    fn center_mouse(&mut self) {
+        self.mouse_x = self.width / 2;
+        self.mouse_y = self.height / 2;
+    }
+
+    fn present(&mut self, framebuffer: &Framebuffer) {
+        self.last_frame.clear();
+        self.last_frame.extend_from_slice(framebuffer.pixels());
+        self.frames_presented += 1;
+        // Las pulsaciones duran un solo frame
+        self.keys_pressed.clear();
+    }
+
+    fn load_sound(&mut self, _name: &str, _path: &str, _volume: f32) {}
+
+    fn play_effect(&mut self, name: &str) {
+        self.effects.push(name.to_string());
+    }
+
+    fn play_music(&mut self, path: &str) {
+        self.music = Some(path.to_string());
+    }
+}
@@ -0,0 +1,64 @@
+//! Separación entre el núcleo del juego y el sistema: entrada, presentación
+//! del framebuffer y audio. El juego solo habla con el trait `Platform`.
+
+use crate::framebuffer::Framebuffer;
+
+pub mod headless;
+#[cfg(feature = "raylib-backend")]
+pub mod raylib_backend;
+
+/// Teclas que usa el juego, independientes del backend
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    W,
+    A,
+    S,
+    D,
+    Q,
+    E,
+    F,
+    C,
+    B,
+    M,
+    T,
+    P,
+    Up,
+    Down,
+    Left,
+    Right,
+    Tab,
+    Space,
+    LeftControl,
+    F10,
+    F12,
+}
+
+pub trait Platform {
+    /// El usuario pidió cerrar la ventana (o el backend terminó)
+    fn should_close(&self) -> bool;
+    /// Segundos reales que duró el último frame
+    fn frame_time(&self) -> f32;
+    fn fps(&self) -> u32;
+
+    // === ENTRADA ===
+    fn is_key_down(&self, key: Key) -> bool;
+    /// La tecla se presionó durante el último frame
+    fn is_key_pressed(&self, key: Key) -> bool;
+    fn mouse_x(&self) -> i32;
+    fn mouse_y(&self) -> i32;
+    fn screen_width(&self) -> i32;
+    fn screen_height(&self) -> i32;
+    fn center_mouse(&mut self);
+    /// Mostrar el cursor (el menú lo usa). Sin ventana no hay nada que mostrar
+    #[allow(dead_code)] // solo la llama el menú de la ventana
+    fn show_cursor(&mut self) {}
+
+    // === PRESENTACIÓN ===
+    /// Mostrar el framebuffer terminado y avanzar al siguiente frame
+    fn present(&mut self, framebuffer: &Framebuffer);
+
+    // === AUDIO ===
+    fn load_sound(&mut self, name: &str, path: &str, volume: f32);
+    fn play_effect(&mut self, name: &str);
+    fn play_music(&mut self, path: &str);
+}
@@ -0,0 +1,140 @@
+use raylib::prelude::*;
+
+use crate::audio_manager::AudioManager;
+use crate::framebuffer::Framebuffer;
+use crate::platform::{Key, Platform};
+
+/// Backend con ventana de raylib y audio de rodio. Las pantallas de menú
+/// usan `rl` y `thread` directamente para dibujar con raylib.
+pub struct RaylibPlatform {
+    pub rl: RaylibHandle,
+    pub thread: RaylibThread,
+    screen: Option<Texture2D>,  // Textura reutilizable donde se sube el framebuffer
+    audio: AudioManager,
+}
+
+impl RaylibPlatform {
+    pub fn new(width: i32, height: i32, title: &str) -> Self {
+        let (mut rl, thread) = raylib::init()
+            .size(width, height)
+            .title(title)
+            .log_level(TraceLogLevel::LOG_WARNING)
+            .build();
+
+        rl.set_target_fps(60);
+        rl.hide_cursor();
+
+        RaylibPlatform {
+            rl,
+            thread,
+            screen: None,
+            audio: AudioManager::new(),
+        }
+    }
+}
+
+fn raylib_key(key: Key) -> KeyboardKey {
+    match key {
+        Key::W => KeyboardKey::KEY_W,
+        Key::A => KeyboardKey::KEY_A,
+        Key::S => KeyboardKey::KEY_S,
+        Key::D => KeyboardKey::KEY_D,
+        Key::Q => KeyboardKey::KEY_Q,
+        Key::E => KeyboardKey::KEY_E,
+        Key::F => KeyboardKey::KEY_F,
+        Key::C => KeyboardKey::KEY_C,
+        Key::B => KeyboardKey::KEY_B,
+        Key::M => KeyboardKey::KEY_M,
+        Key::T => KeyboardKey::KEY_T,
+        Key::P => KeyboardKey::KEY_P,
+        Key::Up => KeyboardKey::KEY_UP,
+        Key::Down => KeyboardKey::KEY_DOWN,
+        Key::Left => KeyboardKey::KEY_LEFT,
+        Key::Right => KeyboardKey::KEY_RIGHT,
+        Key::Tab => KeyboardKey::KEY_TAB,
+        Key::Space => KeyboardKey::KEY_SPACE,
+        Key::LeftControl => KeyboardKey::KEY_LEFT_CONTROL,
+        Key::F10 => KeyboardKey::KEY_F10,
+        Key::F12 => KeyboardKey::KEY_F12,
+    }
+}
+
+impl Platform for RaylibPlatform {
+    fn should_close(&self) -> bool {
+        self.rl.window_should_close()
+    }
+
+    fn frame_time(&self) -> f32 {
+        self.rl.get_frame_time()
+    }
+
+    fn fps(&self) -> u32 {
+        self.rl.get_fps()
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.rl.is_key_down(raylib_key(key))
+    }
+
+    fn is_key_pressed(&self, key: Key) -> bool {
+        self.rl.is_key_pressed(raylib_key(key))
+    }
+
+    fn mouse_x(&self) -> i32 {
+        self.rl.get_mouse_x()
+    }
+
+    fn mouse_y(&self) -> i32 {
+        self.rl.get_mouse_y()
+    }
+
+    fn screen_width(&self) -> i32 {
+        self.rl.get_screen_width()
+    }
+
+    fn screen_height(&self) -> i32 {
+        self.rl.get_screen_height()
+    }
+
+    fn center_mouse(&mut self) {
+        let center = Vector2::new(self.screen_width() as f32 / 2.0, self.screen_height() as f32 / 2.0);
+        self.rl.set_mouse_position(center);
+    }
+
+    fn show_cursor(&mut self) {
+        self.rl.show_cursor();
+    }
+
+    fn present(&mut self, framebuffer: &Framebuffer) {
+        // Crear la textura solo la primera vez
+        if self.screen.is_none() {
+            let img = Image::gen_image_color(framebuffer.width as i32, framebuffer.height as i32, Color::WHITE);
+            self.screen = Some(self.rl.load_texture_from_image(&self.thread, &img)
+                .expect("No se pudo crear textura"));
+        }
+
+        if let Some(texture) = &self.screen {
+            // Actualizar la textura existente (mucho más rápido que crear una nueva)
+            unsafe {
+                raylib::ffi::UpdateTexture(*texture.as_ref(), framebuffer.pixels().as_ptr() as *const _);
+            }
+
+            let background = framebuffer.background_color();
+            let mut d = self.rl.begin_drawing(&self.thread);
+            d.clear_background(Color::new(background.r, background.g, background.b, background.a));
+            d.draw_texture(texture, 0, 0, Color::WHITE);
+        }
+    }
+
+    fn load_sound(&mut self, name: &str, path: &str, volume: f32) {
+        self.audio.load_sound(name, path, volume);
+    }
+
+    fn play_effect(&mut self, name: &str) {
+        self.audio.play_effect(name);
+    }
+
+    fn play_music(&mut self, path: &str) {
+        self.audio.play_music(path);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::capture::FrameCapture;
+use crate::clock::GameClock;
+use crate::events::{read_input, update_player, occupies_cell, PlayerInput};
+use crate::framebuffer::Framebuffer;
+use crate::game::{render_maze, GameState, Level, LevelStats};
+use crate::hud::{draw_debug_text, draw_hud, draw_pause, Messages};
+use crate::lights::{sprite_lights, LightMap};
+use crate::math::{Color, Vector2};
+use crate::platform::{Key, Platform};
+use crate::player::Player;
+use crate::replay::InputTape;
+use crate::renderer::{render_world, render_world_with_textures_downscale};
+use crate::sprites::{render_sprites, collect_pickups, apply_sprite_damage, SPRITE_KINDS};
+use crate::texture::TextureManager;
+
+/// Cargar las texturas de todos los tipos de sprite (`./textures/sprites/<frame>.png`)
+pub fn load_sprite_textures(texture_manager: &mut TextureManager) {
+    for kind in SPRITE_KINDS.iter() {
+        for frame in kind.frames.iter() {
+            let path = format!("./textures/sprites/{}.png", frame);
+            if let Err(e) = texture_manager.load_sprite_texture(frame, &path) {
+                println!("{}", e);
+            }
+        }
+    }
+}
+
+/// Efectos de sonido usados durante el juego
+pub fn load_sounds(platform: &mut dyn Platform) {
+    platform.load_sound("hurt1", "./audio/sound/hurt1.wav", 0.8);
+    platform.load_sound("hurt2", "./audio/sound/hurt2.wav", 0.8);
+    platform.load_sound("hurt3", "./audio/sound/hurt3.wav", 0.8);
+    platform.load_sound("pickup", "./audio/sound/pickup.wav", 0.7);
+    // platform.load_sound("step1", "./audio/sound/step1.wav", 0.7);
+}
+
+/// Texturas declaradas por el nivel; lo que no declare usa las texturas
+/// generadas por defecto
+pub fn load_level_textures(texture_manager: &mut TextureManager, level: &Level) {
+    texture_manager.generate_default_textures();
+    if let Some(path) = &level.wall_texture {
+        for wall_char in ['-', '|', '+'] {
+            if let Err(e) = texture_manager.load_wall_texture(wall_char, path) {
+                println!("{}", e);
+            }
+        }
+    }
+    if let Some(path) = &level.floor_texture
+        && let Err(e) = texture_manager.load_floor_texture(path)
+    {
+        println!("{}", e);
+    }
+    if let Some(path) = &level.ceiling_texture
+        && let Err(e) = texture_manager.load_ceiling_texture(path)
+    {
+        println!("{}", e);
+    }
+    texture_manager.clear_surface_textures();
+    for path in level.surface_textures.iter() {
+        if let Err(e) = texture_manager.load_surface_texture(path) {
+            println!("{}", e);
+        }
+    }
+}
+
+/// Jugar un nivel hasta que termine. Devuelve el estado al que pasa el
+/// juego: `LevelComplete`, `GameOver`, `Menu` (P o fin de la repetición)
+/// o `Exiting` si se cerró la ventana. `tape` decide si la entrada de cada
+/// paso se graba o se lee de una grabación; `capture` guarda capturas (F12)
+/// y secuencias de frames (F10).
+pub fn play_level(
+    platform: &mut dyn Platform,
+    level: Level,
+    texture_manager: &TextureManager,
+    block_size: usize,
+    stats: &mut LevelStats,
+    tape: &mut InputTape,
+    capture: &mut FrameCapture,
+) -> GameState {
+    if let Some(path) = &level.music {
+        platform.play_music(path);
+    }
+
+    let screen_width = platform.screen_width();
+    let screen_height = platform.screen_height();
+    let level_name = level.name;
+    let mut maze = level.maze;
+    let mut sprites = level.sprites;
+    let mut player = Player::new(level.spawn);
+    player.a = level.facing;
+    let mut previous_pose = player.pose();
+    let mut input = PlayerInput::default();
+    let mut clock = GameClock::new();
+    let mut framebuffer = Framebuffer::new(screen_width as u32, screen_height as u32, Color::BLACK);
+    *stats = LevelStats::default();
+    let mut messages = Messages::default();
+    let mut lights = LightMap::new();
+
+    let mut mode = "3D";
+    let mut use_textures = true;
+
+    platform.center_mouse();
+    let mut last_mouse_x = platform.mouse_x();
+    let mut last_mouse_y = platform.mouse_y();
+
+    while !platform.should_close() {
+        if platform.is_key_pressed(Key::M) {
+            mode = if mode == "2D" { "3D" } else { "2D" };
+        }
+        if platform.is_key_pressed(Key::T) {
+            use_textures = !use_textures;
+        }
+        if platform.is_key_pressed(Key::B) {
+            player.head_bob = !player.head_bob;
+        }
+        if platform.is_key_pressed(Key::Tab) {
+            clock.toggle_pause();
+        }
+        if platform.is_key_pressed(Key::P) {
+            return GameState::Menu;
+        }
+
+        read_input(platform, &mut input, &mut last_mouse_x, &mut last_mouse_y);
+        if clock.paused {
+            input = PlayerInput::default();
+        }
+
+        // === SIMULACIÓN A PASO FIJO ===
+        for _ in 0..clock.advance(platform.frame_time()) {
+            let dt = GameClock::STEP;
+            if !tape.next_step(&mut input) {
+                stats.elapsed = clock.time;
+                return GameState::Menu;
+            }
+            clock.time += dt;
+            previous_pose = player.pose();
+            update_player(&mut player, &mut maze, &mut input, dt, block_size);
+            maze.update_doors(dt, |door| {
+                occupies_cell(player.pos, door.col, door.row, block_size)
+            });
+            for sprite in sprites.iter_mut() {
+                sprite.update(dt);
+            }
+
+            // Objetos recogibles: aplicar efecto, sonido y mensaje
+            messages.update(dt);
+            for pickup in collect_pickups(&mut sprites, &mut player, block_size as f32 * 0.5) {
+                stats.pickups += 1;
+                stats.score += pickup.score;
+                if let Some(sound) = &pickup.sound {
+                    platform.play_effect(sound);
+                }
+                messages.push(&pickup.message);
+            }
+
+            // Daño por contacto con pinchos y fuego
+            player.update(dt);
+            if let Some(damage) = apply_sprite_damage(&sprites, &mut player, block_size as f32 * 0.4) {
+                stats.damage_taken += damage;
+                platform.play_effect(&format!("hurt{}", rand::random_range(1..=3)));
+            }
+            if player.is_dead() {
+                stats.elapsed = clock.time;
+                return GameState::GameOver;
+            }
+
+            // Llegar a la salida termina el nivel
+            if maze.cell_at(player.pos.x, player.pos.y, block_size) == Some('X') {
+                stats.elapsed = clock.time;
+                return GameState::LevelComplete;
+            }
+        }
+        stats.elapsed = clock.time;
+
+        // === DIBUJO ===
+        // Interpolar entre los dos últimos pasos para un movimiento suave
+        let view = player.interpolated(previous_pose, clock.alpha());
+        framebuffer.clear();
+        if mode == "2D" {
+            render_maze(&mut framebuffer, &maze, &view, block_size, block_size, Vector2::new(0.0, 0.0), true);
+        } else {
+            if use_textures {
+                lights.update(&maze, &sprite_lights(&sprites, block_size), block_size);
+                let depth_buffer = render_world_with_textures_downscale(&mut framebuffer, &maze, &view, block_size, texture_manager, &lights);
+                render_sprites(&mut framebuffer, &sprites, &view, texture_manager, &depth_buffer, block_size);
+            } else {
+                render_world(&mut framebuffer, &maze, &view, block_size);
+            }
+            render_maze(&mut framebuffer, &maze, &view, block_size - 20, block_size, Vector2::new((screen_width - 310) as f32, 0.0), false);
+        }
+        draw_hud(&mut framebuffer, &view, &maze, &level_name, stats, &messages);
+        if clock.paused {
+            draw_pause(&mut framebuffer);
+        }
+        let mut debug_lines = vec![
+            format!("FPS: {}", platform.fps()),
+            format!("Modo: {}", mode),
+            format!("Texturas: {}", if use_textures { "ON" } else { "OFF" }),
+            format!("Balanceo: {}", if player.head_bob { "ON" } else { "OFF" }),
+        ];
+        if let Some((tick, total)) = tape.progress() {
+            debug_lines.push(format!("Repetición: {}/{}", tick, total));
+        }
+        if capture.is_recording() {
+            debug_lines.push(format!("Secuencia: {} frames", capture.saved_frames()));
+        }
+        draw_debug_text(
+            &mut framebuffer,
+            &debug_lines,
+            "M: Cambiar modo | T: Texturas | B: Balanceo | F: Abrir puerta | ESPACIO: Saltar | C: Agacharse | TAB: Pausa | P: Menú | F12: Captura | F10: Secuencia",
+        );
+
+        // === CAPTURAS ===
+        if platform.is_key_pressed(Key::F12) {
+            match capture.screenshot(&framebuffer) {
+                Ok(path) => messages.push(&format!("Captura guardada: {}", path.display())),
+                Err(e) => println!("{}", e),
+            }
+        }
+        if platform.is_key_pressed(Key::F10) {
+            // El aviso de inicio solo va a la consola para no quedar en la secuencia
+            match capture.toggle_sequence() {
+                Ok(message) if capture.is_recording() => println!("{}", message),
+                Ok(message) => {
+                    println!("{}", message);
+                    messages.push(&message);
+                }
+                Err(e) => println!("{}", e),
+            }
+        }
+        capture.capture(&framebuffer);
+
+        platform.present(&framebuffer);
+    }
+
+    GameState::Exiting
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::parse_level;
+    use crate::platform::headless::HeadlessPlatform;
+
+    /// Pasillo de tres celdas con la salida al final
+    const CORRIDOR: &str = "@spawn 1 1 0\n+--+--+\n|    X|\n+--+--+\n";
+
+    fn play(platform: &mut HeadlessPlatform) -> (GameState, LevelStats) {
+        let level = parse_level(CORRIDOR, 30).unwrap();
+        let texture_manager = TextureManager::new();
+        let mut stats = LevelStats::default();
+        let mut capture = FrameCapture::new(1);
+        platform.max_frames = Some(300);
+        let state = play_level(platform, level, &texture_manager, 30, &mut stats, &mut InputTape::Live, &mut capture);
+        (state, stats)
+    }
+
+    #[test]
+    fn holding_forward_reaches_the_exit() {
+        let mut platform = HeadlessPlatform::new(320, 200);
+        platform.hold(Key::W);
+        let (state, stats) = play(&mut platform);
+        assert!(state == GameState::LevelComplete);
+        assert!(stats.elapsed > 0.0 && stats.elapsed < 2.0);
+    }
+
+    #[test]
+    fn walking_away_never_finishes() {
+        let mut platform = HeadlessPlatform::new(320, 200);
+        platform.hold(Key::S);
+        assert!(play(&mut platform).0 == GameState::Exiting);
+        assert_eq!(platform.frames_presented, 300);
+    }
+
+    #[test]
+    fn pause_key_returns_to_the_menu() {
+        let mut platform = HeadlessPlatform::new(320, 200);
+        platform.tap(Key::P);
+        assert!(play(&mut platform).0 == GameState::Menu);
+        assert_eq!(platform.frames_presented, 0);
+    }
+}
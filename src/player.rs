@@ -1,20 +1,172 @@
-use raylib::prelude::*;
+use crate::math::{Color, Vector2};
 use std::f32::consts::PI;
 use crate::framebuffer::{Framebuffer, rgba_to_u32};
 
+#[derive(Clone)]
 pub struct Player{
     pub pos: Vector2,
     pub a: f32,
-    pub fov: f32
+    pub fov: f32,
+    pub pitch: f32, // horizonte desplazado en fracciones del alto de pantalla (positivo: mirar arriba)
+    pub z: f32,      // altura de los pies sobre el suelo al saltar, en fracciones del alto de pared
+    pub vz: f32,     // velocidad vertical en fracciones del alto de pared por segundo
+    pub crouch: f32, // 0 de pie, 1 agachado; cambia de a poco
+    pub bob: f32,    // desplazamiento actual de la cabeza al caminar
+    pub bob_phase: f32,
+    pub head_bob: bool,
+    pub keys: Vec<char>, // ids de las llaves que lleva
+    pub health: i32,
+    pub max_health: i32,
+    pub invulnerable: f32, // segundos restantes sin recibir daño
+}
+
+/// Posición y ángulo del jugador en un paso de simulación
+#[derive(Clone, Copy)]
+pub struct PlayerPose {
+    pub pos: Vector2,
+    pub a: f32,
+    pub pitch: f32,
+    pub z: f32,
+    pub crouch: f32,
+    pub bob: f32,
 }
 
 impl Player {
+    /// Segundos de invulnerabilidad después de recibir un golpe
+    pub const INVULNERABILITY_TIME: f32 = 1.0;
+    /// Cuánto se puede mirar hacia arriba o abajo
+    pub const MAX_PITCH: f32 = 0.4;
+    /// Altura de los ojos de pie y agachado, en fracciones del alto de pared
+    pub const STAND_EYE_HEIGHT: f32 = 0.5;
+    pub const CROUCH_EYE_HEIGHT: f32 = 0.3;
+    /// Velocidad inicial del salto y gravedad (alto de pared por segundo)
+    const JUMP_SPEED: f32 = 1.3;
+    const GRAVITY: f32 = 4.5;
+    /// Cuánto tarda en agacharse o pararse del todo, en segundos
+    const CROUCH_TIME: f32 = 0.15;
+    /// Pasos por segundo (medio ciclo de seno cada uno) y altura del balanceo
+    const BOB_STEPS_PER_SECOND: f32 = 3.5;
+    const BOB_AMPLITUDE: f32 = 0.012;
+
     pub fn new(pos: Vector2) -> Self{
         Player { 
             pos,
             a: 0.0,
-            fov: PI / 3.0
+            fov: PI / 3.0,
+            pitch: 0.0,
+            z: 0.0,
+            vz: 0.0,
+            crouch: 0.0,
+            bob: 0.0,
+            bob_phase: 0.0,
+            head_bob: true,
+            keys: Vec::new(),
+            health: 100,
+            max_health: 100,
+            invulnerable: 0.0,
+        }
+    }
+
+    /// Recibir daño si no está en periodo de invulnerabilidad.
+    /// Devuelve true si el golpe se aplicó.
+    pub fn take_damage(&mut self, amount: i32) -> bool {
+        if self.invulnerable > 0.0 || self.is_dead() {
+            return false;
+        }
+        self.health = (self.health - amount).max(0);
+        self.invulnerable = Self::INVULNERABILITY_TIME;
+        true
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.health <= 0
+    }
+
+    pub fn pose(&self) -> PlayerPose {
+        PlayerPose { pos: self.pos, a: self.a, pitch: self.pitch, z: self.z, crouch: self.crouch, bob: self.bob }
+    }
+
+    /// Copia del jugador entre `previous` y el estado actual (`alpha` de 0 a 1),
+    /// para dibujar entre dos pasos de simulación
+    pub fn interpolated(&self, previous: PlayerPose, alpha: f32) -> Player {
+        let mut view = self.clone();
+        view.pos = Vector2::new(
+            previous.pos.x + (self.pos.x - previous.pos.x) * alpha,
+            previous.pos.y + (self.pos.y - previous.pos.y) * alpha,
+        );
+
+        // Girar por el camino más corto aunque el ángulo haya dado la vuelta
+        let mut delta = self.a - previous.a;
+        if delta > PI {
+            delta -= 2.0 * PI;
+        } else if delta < -PI {
+            delta += 2.0 * PI;
         }
+        view.a = previous.a + delta * alpha;
+        view.pitch = previous.pitch + (self.pitch - previous.pitch) * alpha;
+        view.z = previous.z + (self.z - previous.z) * alpha;
+        view.crouch = previous.crouch + (self.crouch - previous.crouch) * alpha;
+        view.bob = previous.bob + (self.bob - previous.bob) * alpha;
+        view
+    }
+
+    /// Fila de pantalla del horizonte. Mirar arriba o abajo desplaza toda
+    /// la imagen en vertical (y-shearing) en vez de rotar la cámara.
+    pub fn horizon(&self, screen_height: f32) -> f32 {
+        screen_height / 2.0 + self.pitch * screen_height
+    }
+
+    /// Altura de los ojos sobre el suelo en fracciones del alto de pared,
+    /// contando salto, agachado y balanceo al caminar
+    pub fn eye_height(&self) -> f32 {
+        Self::STAND_EYE_HEIGHT + (Self::CROUCH_EYE_HEIGHT - Self::STAND_EYE_HEIGHT) * self.crouch + self.z + self.bob
+    }
+
+    pub fn on_ground(&self) -> bool {
+        self.z <= 0.0 && self.vz <= 0.0
+    }
+
+    /// Avanzar la altura de la cámara durante `dt` segundos: salto con
+    /// gravedad, agacharse mientras se mantiene `crouch` y el balanceo de
+    /// la cabeza si `walking`. Solo se salta desde el suelo y de pie.
+    pub fn update_height(&mut self, dt: f32, jump: bool, crouch: bool, walking: bool) {
+        if jump && self.on_ground() && self.crouch == 0.0 {
+            self.vz = Self::JUMP_SPEED;
+        }
+        if !self.on_ground() {
+            self.vz -= Self::GRAVITY * dt;
+            self.z += self.vz * dt;
+            if self.z <= 0.0 {
+                self.z = 0.0;
+                self.vz = 0.0;
+            }
+        }
+
+        let target = if crouch && self.on_ground() { 1.0 } else { 0.0 };
+        let step = dt / Self::CROUCH_TIME;
+        self.crouch = if self.crouch < target {
+            (self.crouch + step).min(target)
+        } else {
+            (self.crouch - step).max(target)
+        };
+
+        // El balanceo termina el paso en curso antes de detenerse
+        let bob_step = Self::BOB_STEPS_PER_SECOND * PI * dt;
+        if walking && self.on_ground() {
+            self.bob_phase += bob_step;
+        } else {
+            let rest = (self.bob_phase / PI).ceil() * PI;
+            self.bob_phase = (self.bob_phase + bob_step).min(rest);
+        }
+        if self.bob_phase >= 2.0 * PI {
+            self.bob_phase -= 2.0 * PI;
+        }
+        self.bob = if self.head_bob { -self.bob_phase.sin().abs() * Self::BOB_AMPLITUDE } else { 0.0 };
+    }
+
+    /// Avanzar temporizadores del jugador
+    pub fn update(&mut self, dt: f32) {
+        self.invulnerable = (self.invulnerable - dt).max(0.0);
     }
 
     pub fn draw_player_triangle(
@@ -1,117 +1,122 @@
 use crate::framebuffer::{Framebuffer, rgba_to_u32};
 use crate::player::Player;
 use crate::game::Maze;
-use crate::cast::{cast_ray};
+use crate::cast::{cast_ray_steps, Intersect, RayStep};
 use crate::texture::TextureManager;
+use crate::sprites::multiply_color;
+use crate::lights::LightMap;
 
-use raylib::prelude::*;
+/// Tinte verde del suelo de la salida del nivel (RGBA como u32)
+const EXIT_TINT: u32 = 0xFF78FF78;
+/// Oscurecimiento del fondo de los fosos
+const PIT_TINT: u32 = 0xFF505050;
 
+/// Render a resolución completa, un rayo por columna. El juego usa el
+/// reducido; este queda como referencia de las imágenes golden.
+#[allow(dead_code)]
 pub fn render_world_with_textures(
     framebuffer: &mut Framebuffer,
     maze: &Maze,
     player: &Player,
     block_size: usize,
     texture_manager: &TextureManager,
+    lights: &LightMap,
 ) {
     let screen_width = framebuffer.width;
     let screen_height = framebuffer.height;
     let num_rays = screen_width;
     let fov = player.fov;
-    let half_screen_height = screen_height as f32 / 2.0;
+    let stop_height = maze.max_wall_height().max(player.eye_height());
+    let mut depth_buffer = vec![f32::INFINITY; (screen_width * screen_height) as usize];
+    let mut angles = Vec::with_capacity(num_rays as usize);
+    let mut column = Column::new(screen_height as usize);
+    let mut steps = Vec::new();
 
     for i in 0..num_rays {
         let ray_ratio = i as f32 / num_rays as f32;
         let ray_angle = player.a - (fov / 2.0) + (ray_ratio * fov);
 
-        let intersection = cast_ray(
-            framebuffer,
-            maze,
-            player,
-            block_size,
-            ray_angle,
-            Vector2::new(0.0, 0.0),
-            false,
-        );
-
-        let corrected_distance = intersection.distance * (player.a - ray_angle).cos();
-
-        // Calcular alturas de pared, techo y suelo
-        let wall_height = if corrected_distance > 0.1 {
-            (block_size as f32 * screen_height as f32) / corrected_distance
+        cast_ray_steps(maze, player.pos, ray_angle, block_size, stop_height, &mut steps);
+
+        // Efecto linterna
+        let dx = (i as f32 / screen_width as f32) - 0.5;
+        let flashlight_width = 0.12; // Haz estrecho
+        let flashlight_strength = 0.7; // Qué tan brillante es el centro
+
+        let flashlight_factor = if dx.abs() < flashlight_width {
+            // Atenuación suave en los bordes del haz
+            flashlight_strength * (1.0 - dx.abs() / flashlight_width)
         } else {
-            screen_height as f32
+            0.0
         };
 
-        let wall_start = (half_screen_height - wall_height / 2.0).max(0.0) as u32;
-        let wall_end = (half_screen_height + wall_height / 2.0).min(screen_height as f32) as u32;
-
-        // Calcular coordenada de textura horizontal (qué parte de la pared estamos viendo)
-        let hit_x = player.pos.x + intersection.distance * ray_angle.cos();
-        let hit_y = player.pos.y + intersection.distance * ray_angle.sin();
-        
-        let (wall_x, actual_wall_char) = determine_wall_orientation_and_texture(
-            maze, 
-            hit_x, 
-            hit_y, 
-            block_size, 
-            intersection.impact,
-            ray_angle
-        );
-
-        // === RENDERIZAR TECHO ===
-        for y in 0..wall_start {
-            let ceiling_color = texture_manager.get_ceiling_color(
-                i as f32, 
-                y as f32, 
-                screen_width as f32, 
-                screen_height as f32
-            );
-            framebuffer.set_pixel_fast(i as u32, y, ceiling_color);
-        }
+        // === RENDERIZAR PAREDES CON TEXTURA ===
+        column.clear();
+        draw_ray_steps(&mut column, &steps, ray_angle, player, maze, block_size, Some((texture_manager, lights)), flashlight_factor);
+        column.copy_to(framebuffer.pixels_mut(), &mut depth_buffer, i as usize, screen_width as usize);
 
-        // === RENDERIZAR PARED CON TEXTURA ===
-        if wall_start < wall_end {
-            for y in wall_start..wall_end {
-                let wall_progress = (y - wall_start) as f32 / (wall_end - wall_start) as f32;
-                let wall_color = texture_manager.get_wall_color(actual_wall_char, wall_x, wall_progress);
-
-                // Efecto linterna
-                let dx = (i as f32 / screen_width as f32) - 0.5;
-                let flashlight_angle = 0.0; // Centrado
-                let flashlight_width = 0.12; // Haz estrecho
-                let flashlight_strength = 0.7; // Qué tan brillante es el centro
-
-                let flashlight_factor = if dx.abs() < flashlight_width {
-                    // Atenuación suave en los bordes del haz
-                    flashlight_strength * (1.0 - dx.abs() / flashlight_width)
-                } else {
-                    0.0
-                };
+        angles.push(ray_angle);
+    }
 
-                let shaded_color = apply_distance_shading(wall_color, corrected_distance, flashlight_factor);
-                framebuffer.set_pixel_fast(i as u32, y, shaded_color);
-            }
-        }
+    // === RENDERIZAR SUELO Y TECHO === por filas, con las columnas ya conocidas
+    let stride = screen_width as usize;
+    cast_floor_and_ceiling(framebuffer.pixels_mut(), &mut depth_buffer, stride, &angles, maze, player, block_size, texture_manager, lights);
+}
 
-        // === RENDERIZAR SUELO === Esto causa la bajada de FPS tener en cuenta
-        for y in wall_end..screen_height {
-            let ray_dir_x = ray_angle.cos();
-            let ray_dir_y = ray_angle.sin();
+/// Columnas de pantalla que comparten un mismo rayo en el render reducido
+const RAY_STEP: usize = 2; // 1 = todos los rayos, 2 = mitad de rayos
+
+/// Franja de columnas contiguas que dibuja un solo hilo. Cada hilo escribe
+/// en sus propios píxeles y profundidades (fila por fila, solo el ancho de
+/// la franja); al final se copian al framebuffer y al depth buffer.
+struct ColumnStrip {
+    x0: usize,                  // primera columna de la franja en pantalla
+    width: usize,
+    screen_width: usize,
+    pixels: Vec<u32>,
+    depth: Vec<f32>,
+    angles: Vec<f32>,           // ángulo del rayo de cada columna de la franja
+}
 
-            let p = y as f32 - half_screen_height;
-            let pos_z = 0.5 * screen_height as f32;
-            let row_distance = pos_z / p;
+/// Píxeles y profundidad de una sola columna de pantalla mientras se
+/// dibujan las paredes de su rayo
+struct Column {
+    pixels: Vec<u32>,
+    depth: Vec<f32>,
+}
 
-            let floor_x = player.pos.x + row_distance * ray_dir_x;
-            let floor_y = player.pos.y + row_distance * ray_dir_y;
+impl Column {
+    fn new(screen_height: usize) -> Self {
+        Column { pixels: vec![0; screen_height], depth: vec![f32::INFINITY; screen_height] }
+    }
 
-            let floor_color = texture_manager.get_floor_color(floor_x, floor_y);
-            let shaded_floor_color = apply_distance_shading(floor_color, row_distance, 0.0);
-            framebuffer.set_pixel_fast(i as u32, y, shaded_floor_color);
+    fn clear(&mut self) {
+        self.depth.fill(f32::INFINITY);
+    }
+
+    #[inline]
+    fn set(&mut self, y: usize, color: u32, distance: f32) {
+        self.pixels[y] = color;
+        self.depth[y] = distance;
+    }
+
+    /// Copiar lo dibujado a la columna `x` de buffers con `stride` píxeles
+    /// por fila; lo que quedó libre es para el suelo, el techo o el cielo
+    fn copy_to(&self, pixels: &mut [u32], depth: &mut [f32], x: usize, stride: usize) {
+        for (y, &distance) in self.depth.iter().enumerate() {
+            if distance < f32::INFINITY {
+                pixels[y * stride + x] = self.pixels[y];
+                depth[y * stride + x] = distance;
+            }
         }
     }
 }
 
+/// Hilos que usa el render reducido: uno por núcleo disponible
+pub fn render_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
 //Downscaling horizontal con ray_step
 pub fn render_world_with_textures_downscale(
     framebuffer: &mut Framebuffer,
@@ -119,299 +124,381 @@ pub fn render_world_with_textures_downscale(
     player: &Player,
     block_size: usize,
     texture_manager: &TextureManager,
+    lights: &LightMap,
 ) -> Vec<f32>{
+    render_world_with_textures_threaded(framebuffer, maze, player, block_size, texture_manager, lights, render_threads())
+}
+
+/// Render reducido repartiendo las columnas en `threads` hilos. Cada hilo
+/// se queda con una franja de rayos virtuales contiguos y devuelve sus
+/// píxeles. El depth buffer tiene la distancia de cada píxel (fila por
+/// fila) para que los sprites se vean por encima de las paredes bajas.
+pub fn render_world_with_textures_threaded(
+    framebuffer: &mut Framebuffer,
+    maze: &Maze,
+    player: &Player,
+    block_size: usize,
+    texture_manager: &TextureManager,
+    lights: &LightMap,
+    threads: usize,
+) -> Vec<f32> {
     let screen_width = framebuffer.width as usize;
     let screen_height = framebuffer.height as usize;
-    let ray_step: usize = 2; // <-- configurable: 1 = full, 2 = mitad de rayos
-    let virtual_rays = (screen_width + ray_step - 1) / ray_step;
-    let fov = player.fov;
-    let half_screen_height = screen_height as f32 / 2.0;
-    let mut depth_buffer = vec![f32::INFINITY; screen_width];
-
-    // Para cada rayo "virtual"
-    for vr in 0..virtual_rays {
-        // coordenada x izquierda del bloque en pantalla
-        let block_x = vr * ray_step;
-        // usamos el píxel central del bloque para el ángulo (reduce jitter)
-        let center_x = (block_x as f32 + (ray_step as f32) / 2.0).min(screen_width as f32 - 1.0);
-        let ray_ratio = center_x / screen_width as f32;
-        let ray_angle = player.a - (fov / 2.0) + (ray_ratio * fov);
-
-        // lanzar rayo (un solo cast por bloque)
-        let intersection = cast_ray(
-            framebuffer,
-            maze,
-            player,
-            block_size,
-            ray_angle,
-            Vector2::new(0.0, 0.0),
-            false,
-        );
-        let corrected_distance = intersection.distance * (player.a - ray_angle).cos();
-
-        for dx in 0..ray_step {
-            let px = block_x + dx;
-            if px < screen_width {
-                depth_buffer[px] = corrected_distance;
-            }
-        }
-
-        // altura de pared
-        let wall_height = if corrected_distance > 0.1 {
-            (block_size as f32 * screen_height as f32) / corrected_distance
-        } else {
-            screen_height as f32
-        };
-
-        let wall_start = (half_screen_height - wall_height / 2.0).max(0.0) as usize;
-        let wall_end = (half_screen_height + wall_height / 2.0).min(screen_height as f32) as usize;
-
-        // hit coords para calcular wall_x (u)
-        let hit_x = player.pos.x + intersection.distance * ray_angle.cos();
-        let hit_y = player.pos.y + intersection.distance * ray_angle.sin();
-        let (wall_x, actual_wall_char) = determine_wall_orientation_and_texture(
-            maze, hit_x, hit_y, block_size, intersection.impact, ray_angle
-        );
-
-        // RENDER TECHO para todo el bloque (replicar horizontalmente)
-        for y in 0..wall_start {
-            let ceiling_color = texture_manager.get_ceiling_color(
-                center_x, y as f32, screen_width as f32, screen_height as f32
-            );
-            for dx in 0..ray_step {
-                let px = block_x + dx;
-                if px < screen_width { framebuffer.set_pixel_fast(px as u32, y as u32, ceiling_color); }
-            }
-        }
-
-        // RENDER PARED texturada (pixel-por-pixel vertical, pero replicada horizontalmente)
-        if wall_start < wall_end {
-            for y in wall_start..wall_end {
-                let wall_progress = (y - wall_start) as f32 / ((wall_end - wall_start) as f32).max(1.0);
-                let wall_color = texture_manager.get_wall_color(actual_wall_char, wall_x, wall_progress);
-
-                // sombreado por distancia (puedes extraer y optimizar esto)
-                let dx_center = (center_x / screen_width as f32) - 0.5;
-                let flashlight_width = 0.12;
-                let flashlight_strength = 0.6;
-                let flashlight_factor = if dx_center.abs() < flashlight_width {
-                    flashlight_strength * (1.0 - dx_center.abs() / flashlight_width)
-                } else { 0.0 };
-
-                let shaded_color = apply_distance_shading(wall_color, corrected_distance, flashlight_factor);
-
-                // replicar la columna en ray_step píxeles horizontales
-                for dx in 0..ray_step {
-                    let px = block_x + dx;
-                    if px < screen_width {
-                        framebuffer.set_pixel_fast(px as u32, y as u32, shaded_color);
+    let virtual_rays = screen_width.div_ceil(RAY_STEP);
+
+    // Las franjas empiezan en el borde de un bloque para no partir rayos
+    let strip_width = virtual_rays.div_ceil(threads.max(1)) * RAY_STEP;
+    let strip_count = screen_width.div_ceil(strip_width);
+
+    let strips: Vec<ColumnStrip> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..strip_count)
+            .map(|i| {
+                scope.spawn(move || {
+                    let x0 = i * strip_width;
+                    let width = strip_width.min(screen_width - x0);
+                    let mut strip = ColumnStrip {
+                        x0,
+                        width,
+                        screen_width,
+                        pixels: vec![0; width * screen_height],
+                        depth: vec![f32::INFINITY; width * screen_height],
+                        angles: Vec::with_capacity(width),
+                    };
+                    let mut column = Column::new(screen_height);
+                    let mut steps = Vec::new();
+                    let first_ray = strip.x0 / RAY_STEP;
+                    let last_ray = (strip.x0 + width).div_ceil(RAY_STEP);
+                    for vr in first_ray..last_ray {
+                        render_textured_ray(&mut strip, &mut column, &mut steps, vr, maze, player, block_size, texture_manager, lights);
                     }
-                }
-            }
-        }
-
-        // RENDER SUELO (similar: calculas por rayo virtual y replicar)
-        for y in wall_end..screen_height {
-            let ray_dir_x = ray_angle.cos();
-            let ray_dir_y = ray_angle.sin();
-            let p = y as f32 - half_screen_height;
-            let pos_z = 0.5 * screen_height as f32;
-            let row_distance = pos_z / p;
-
-            let floor_x = player.pos.x + row_distance * ray_dir_x;
-            let floor_y = player.pos.y + row_distance * ray_dir_y;
-
-            let floor_color = texture_manager.get_floor_color(floor_x, floor_y);
-            let shaded_floor_color = apply_distance_shading(floor_color, row_distance, 0.0);
-
-            for dx in 0..ray_step {
-                let px = block_x + dx;
-                if px < screen_width {
-                    framebuffer.set_pixel_fast(px as u32, y as u32, shaded_floor_color);
-                }
-            }
+                    cast_floor_and_ceiling(&mut strip.pixels, &mut strip.depth, width, &strip.angles, maze, player, block_size, texture_manager, lights);
+                    strip
+                })
+            })
+            .collect();
+        workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+    });
+
+    let mut depth_buffer = vec![f32::INFINITY; screen_width * screen_height];
+    for strip in strips.iter() {
+        framebuffer.copy_strip(strip.x0 as u32, strip.width as u32, &strip.pixels);
+        for y in 0..screen_height {
+            let row = y * screen_width + strip.x0;
+            depth_buffer[row..row + strip.width].copy_from_slice(&strip.depth[y * strip.width..(y + 1) * strip.width]);
         }
     }
 
     depth_buffer
 }
 
-
-/// Determinar la orientación real de la pared basándose en el contexto del mapa
-fn determine_wall_orientation_and_texture(
+/// Dibujar las paredes de un rayo virtual del render reducido en las
+/// `RAY_STEP` columnas que cubre; suelo y techo se dibujan después por filas
+#[allow(clippy::too_many_arguments)]
+fn render_textured_ray(
+    strip: &mut ColumnStrip,
+    column: &mut Column,
+    steps: &mut Vec<RayStep>,
+    vr: usize,
     maze: &Maze,
-    hit_x: f32,
-    hit_y: f32,
+    player: &Player,
     block_size: usize,
-    impact_char: char,
-    ray_angle: f32,
-) -> (f32, char) {
-    // Convertir coordenadas del mundo a coordenadas del mapa
-    let map_x = (hit_x / block_size as f32).floor() as i32;
-    let map_y = (hit_y / block_size as f32).floor() as i32;
-    
-    // Si no es un '+', usar el carácter original
-    if impact_char != '+' {
-        let wall_x = if impact_char == '|' {
-            hit_y % block_size as f32 / block_size as f32
-        } else {
-            hit_x % block_size as f32 / block_size as f32
-        };
-        return (wall_x, impact_char);
-    }
-    
-    // Para '+', usar método mejorado de detección
-    let context = analyze_wall_context(maze, map_x, map_y);
-    
-    // Calcular distancias a los bordes de la celda
-    let cell_x = hit_x % block_size as f32;
-    let cell_y = hit_y % block_size as f32;
-    let half_block = block_size as f32 / 2.0;
-    
-    let dist_to_left = cell_x;
-    let dist_to_right = block_size as f32 - cell_x;
-    let dist_to_top = cell_y;
-    let dist_to_bottom = block_size as f32 - cell_y;
-    
-    // Encontrar el borde más cercano
-    let min_dist = dist_to_left.min(dist_to_right).min(dist_to_top).min(dist_to_bottom);
-    
-    let (wall_x, wall_type) = if min_dist == dist_to_left || min_dist == dist_to_right {
-        // Golpeamos una cara vertical
-        let wall_x = cell_y / block_size as f32;
-        let wall_type = if context.has_vertical_preference() { '|' } else { '+' };
-        (wall_x, wall_type)
-    } else {
-        // Golpeamos una cara horizontal
-        let wall_x = cell_x / block_size as f32;
-        let wall_type = if context.has_horizontal_preference() { '-' } else { '+' };
-        (wall_x, wall_type)
-    };
-    
-    (wall_x, wall_type)
-}
+    texture_manager: &TextureManager,
+    lights: &LightMap,
+) {
+    let screen_width = strip.screen_width;
+    let fov = player.fov;
 
-/// Estructura para almacenar información del contexto de una pared
-struct WallContext {
-    has_vertical_walls: bool,
-    has_horizontal_walls: bool,
-    is_corner: bool,
-    is_intersection: bool,
-    vertical_connections: usize,
-    horizontal_connections: usize,
+    // coordenada x izquierda del bloque en pantalla
+    let block_x = vr * RAY_STEP;
+    // usamos el píxel central del bloque para el ángulo (reduce jitter)
+    let center_x = (block_x as f32 + (RAY_STEP as f32) / 2.0).min(screen_width as f32 - 1.0);
+    let ray_ratio = center_x / screen_width as f32;
+    let ray_angle = player.a - (fov / 2.0) + (ray_ratio * fov);
+
+    // lanzar rayo (un solo cast por bloque), siguiendo detrás de las paredes bajas
+    let stop_height = maze.max_wall_height().max(player.eye_height());
+    cast_ray_steps(maze, player.pos, ray_angle, block_size, stop_height, steps);
+
+    // sombreado por distancia (puedes extraer y optimizar esto)
+    let dx_center = (center_x / screen_width as f32) - 0.5;
+    let flashlight_width = 0.12;
+    let flashlight_strength = 0.6;
+    let flashlight_factor = if dx_center.abs() < flashlight_width {
+        flashlight_strength * (1.0 - dx_center.abs() / flashlight_width)
+    } else { 0.0 };
+
+    // RENDER PAREDES texturadas (pixel-por-pixel vertical, pero replicadas horizontalmente)
+    column.clear();
+    draw_ray_steps(column, steps, ray_angle, player, maze, block_size, Some((texture_manager, lights)), flashlight_factor);
+
+    let block_end = (block_x + RAY_STEP).min(strip.x0 + strip.width);
+    for px in block_x..block_end {
+        column.copy_to(&mut strip.pixels, &mut strip.depth, px - strip.x0, strip.width);
+        strip.angles.push(ray_angle);
+    }
 }
 
-impl WallContext {
-    fn has_vertical_preference(&self) -> bool {
-        // Preferir vertical si hay más conexiones verticales o es claramente vertical
-        self.vertical_connections > self.horizontal_connections || 
-        (self.has_vertical_walls && !self.has_horizontal_walls)
-    }
-    
-    fn has_horizontal_preference(&self) -> bool {
-        // Preferir horizontal si hay más conexiones horizontales o es claramente horizontal
-        self.horizontal_connections > self.vertical_connections || 
-        (self.has_horizontal_walls && !self.has_vertical_walls)
+/// Alto en pantalla de un bloque a distancia perpendicular `distance`
+#[inline]
+fn projected_height(distance: f32, block_size: usize, screen_height: f32) -> f32 {
+    if distance > 0.1 {
+        (block_size as f32 * screen_height) / distance
+    } else {
+        screen_height
     }
 }
 
-/// Analizar el contexto de una pared '+' para determinar su orientación
-fn analyze_wall_context(maze: &Maze, x: i32, y: i32) -> WallContext {
-    let mut has_vertical_walls = false;
-    let mut has_horizontal_walls = false;
-    let mut vertical_connections = 0;
-    let mut horizontal_connections = 0;
-    
-    // Verificar conexiones específicas
-    let left = get_maze_cell(maze, x - 1, y);
-    let right = get_maze_cell(maze, x + 1, y);
-    let up = get_maze_cell(maze, x, y - 1);
-    let down = get_maze_cell(maze, x, y + 1);
-    
-    // Contar conexiones horizontales (izquierda-derecha)
-    if let Some(cell) = left {
-        if matches!(cell, '|' | '-' | '+') {
-            horizontal_connections += 1;
-            if matches!(cell, '-' | '+') {
-                has_horizontal_walls = true;
-            }
+/// Dibujar en `column` lo que cruza un rayo con ángulo `angle`, de la celda
+/// más cercana a la más lejana: la cara de cada celda más alta que la
+/// anterior, la tapa de las paredes más bajas que los ojos y el fondo de
+/// los fosos. Cada píxel se queda con lo primero que lo cubre, así lo que
+/// está detrás de una pared baja solo asoma por encima.
+///
+/// Sin `textures` (texturas y luces) se usan los colores planos por
+/// distancia.
+#[allow(clippy::too_many_arguments)]
+fn draw_ray_steps(
+    column: &mut Column,
+    steps: &[RayStep],
+    angle: f32,
+    player: &Player,
+    maze: &Maze,
+    block_size: usize,
+    textures: Option<(&TextureManager, &LightMap)>,
+    flashlight: f32,
+) {
+    let screen_height = column.pixels.len() as f32;
+    let horizon = player.horizon(screen_height);
+    let eye = player.eye_height();
+    let block = block_size as f32;
+
+    // Corrección de ojo de pez y dirección para ubicar puntos por distancia
+    // perpendicular (igual que el suelo)
+    let correction = (angle - player.a).cos();
+    let dir = (angle.cos() / correction, angle.sin() / correction);
+
+    // El suelo delante de un desnivel tapa lo que queda más abajo detrás
+    let mut floor_clip = screen_height;
+
+    for step in steps.iter() {
+        let distance = step.hit.distance * correction;
+        let wall_height = projected_height(distance, block_size, screen_height);
+        // Fila de pantalla de la altura `h` sobre la cara de entrada
+        let row_of = |h: f32| horizon - (h - eye) * wall_height;
+
+        if step.height < step.previous {
+            floor_clip = floor_clip.min(row_of(step.previous));
         }
-    }
-    
-    if let Some(cell) = right {
-        if matches!(cell, '|' | '-' | '+') {
-            horizontal_connections += 1;
-            if matches!(cell, '-' | '+') {
-                has_horizontal_walls = true;
+
+        // === CARA VERTICAL === desde la altura anterior hasta la de la celda
+        if step.height > step.previous {
+            let top = row_of(step.height);
+            let bottom = top + (step.height - step.previous) * wall_height;
+            let start = top.clamp(0.0, screen_height) as usize;
+            let end = bottom.min(floor_clip).clamp(0.0, screen_height) as usize;
+
+            // La textura se repite una vez por bloque de alto, medida desde
+            // el borde de una pared normal aunque quede fuera de pantalla
+            let standard_top = row_of(1.0);
+            let ceiling_row = standard_top.clamp(0.0, screen_height) as usize;
+            let wall_char = wall_texture_char(&step.hit);
+            let lock_tint = door_lock_tint(maze, &step.hit);
+            // Luz del lado visible: medio paso delante de la cara
+            let wall_light = textures.map_or([0.0; 3], |(_, lights)| {
+                lights.at(step.hit.hit.x + step.hit.normal.x * 0.5, step.hit.hit.y + step.hit.normal.y * 0.5)
+            });
+
+            for y in start..end {
+                if column.depth[y] < f32::INFINITY {
+                    continue;
+                }
+                let color = match textures {
+                    Some((tm, _)) => {
+                        // Lo que sobresale por encima del techo queda tapado por él
+                        if y < ceiling_row && has_ceiling_at(tm, maze, player, dir, y, horizon, block, screen_height) {
+                            continue;
+                        }
+                        let wall_progress = (y as f32 - standard_top) / wall_height;
+                        let wall_progress = if y < ceiling_row { wall_progress.rem_euclid(1.0) } else { wall_progress };
+                        let mut wall_color = tm.get_wall_color(wall_char, step.hit.tex_u, wall_progress);
+                        if let Some(tint) = lock_tint {
+                            wall_color = multiply_color(wall_color, tint);
+                        }
+                        apply_distance_shading(wall_color, distance, flashlight, wall_light)
+                    }
+                    None => flat_wall_color(distance),
+                };
+                column.set(y, color, distance);
             }
         }
-    }
-    
-    // Contar conexiones verticales (arriba-abajo)
-    if let Some(cell) = up {
-        if matches!(cell, '|' | '-' | '+') {
-            vertical_connections += 1;
-            if matches!(cell, '|' | '+') {
-                has_vertical_walls = true;
+
+        // === TAPA === de las paredes bajas y fondo de los fosos, vistos desde arriba
+        let exit = step.exit * correction;
+        if step.height != 0.0 && step.height < eye && exit > distance {
+            let far = horizon - (step.height - eye) * projected_height(exit, block_size, screen_height);
+            let start = far.clamp(0.0, screen_height) as usize;
+            let end = row_of(step.height).min(floor_clip).clamp(0.0, screen_height) as usize;
+            let wall_char = wall_texture_char(&step.hit);
+
+            for y in start..end {
+                let p = y as f32 + 0.5 - horizon;
+                if column.depth[y] < f32::INFINITY || p <= 0.0 {
+                    continue;
+                }
+                let row_distance = (eye - step.height) * block * screen_height / p;
+                let cell_x = (player.pos.x + row_distance * dir.0) / block;
+                let cell_y = (player.pos.y + row_distance * dir.1) / block;
+                let color = match textures {
+                    Some((tm, lights)) if step.height > 0.0 => {
+                        let light = lights.at_smooth(cell_x * block, cell_y * block);
+                        apply_distance_shading(tm.get_wall_color(wall_char, cell_x, cell_y), row_distance, flashlight, light)
+                    }
+                    Some((tm, lights)) => {
+                        let surface = maze.surface_at(cell_x.max(0.0) as usize, cell_y.max(0.0) as usize);
+                        let floor_color = multiply_color(tm.get_floor_color(surface.floor, cell_x, cell_y), PIT_TINT);
+                        apply_distance_shading(floor_color, row_distance, 0.0, lights.at_smooth(cell_x * block, cell_y * block))
+                    }
+                    None => flat_wall_color(row_distance),
+                };
+                column.set(y, color, row_distance);
             }
         }
     }
-    
-    if let Some(cell) = down {
-        if matches!(cell, '|' | '-' | '+') {
-            vertical_connections += 1;
-            if matches!(cell, '|' | '+') {
-                has_vertical_walls = true;
+}
+
+/// Indica si la fila `y` (sobre el horizonte) de una columna con dirección
+/// `dir` ve techo y no cielo
+#[allow(clippy::too_many_arguments)]
+#[inline]
+fn has_ceiling_at(
+    texture_manager: &TextureManager,
+    maze: &Maze,
+    player: &Player,
+    dir: (f32, f32),
+    y: usize,
+    horizon: f32,
+    block: f32,
+    screen_height: f32,
+) -> bool {
+    let p = horizon - (y as f32 + 0.5);
+    if p <= 0.0 {
+        return false;
+    }
+    let row_distance = (1.0 - player.eye_height()) * block * screen_height / p;
+    let cell_x = (player.pos.x + row_distance * dir.0) / block;
+    let cell_y = (player.pos.y + row_distance * dir.1) / block;
+    let surface = maze.surface_at(cell_x.max(0.0) as usize, cell_y.max(0.0) as usize);
+    texture_manager.has_ceiling(surface.ceiling)
+}
+
+/// Dibujar suelo y techo por filas horizontales en los píxeles que las
+/// paredes dejaron libres (profundidad infinita). La distancia de cada
+/// fila depende solo de su altura en pantalla y de la altura de los ojos
+/// (`pos_z` sobre el suelo, `block - pos_z` bajo el techo), así que se
+/// calcula una vez por fila; cada píxel es el punto del mundo a esa
+/// distancia en la dirección de su columna. Suelo y techo usan las texturas
+/// de la celda que pisa ese punto y se repiten una vez por bloque. Las
+/// celdas sin techo muestran el cielo.
+///
+/// `pixels` y `depth` tienen `stride` píxeles por fila y una columna por
+/// cada elemento de `angles`.
+#[allow(clippy::too_many_arguments)]
+fn cast_floor_and_ceiling(
+    pixels: &mut [u32],
+    depth: &mut [f32],
+    stride: usize,
+    angles: &[f32],
+    maze: &Maze,
+    player: &Player,
+    block_size: usize,
+    texture_manager: &TextureManager,
+    lights: &LightMap,
+) {
+    let screen_height = pixels.len() / stride.max(1);
+    let horizon = player.horizon(screen_height as f32);
+    // El cielo se desplaza con el horizonte
+    let sky_offset = screen_height as f32 / 2.0 - horizon;
+    let block = block_size as f32;
+    let pos_z = player.eye_height() * block;
+
+    // Dirección de cada columna escalada para que `distancia de fila * dir`
+    // caiga sobre el suelo sin efecto ojo de pez (igual que las paredes)
+    let directions: Vec<(f32, f32)> = angles
+        .iter()
+        .map(|&angle| {
+            let correction = (angle - player.a).cos();
+            (angle.cos() / correction, angle.sin() / correction)
+        })
+        .collect();
+
+    for y in 0..screen_height {
+        let is_floor = y as f32 + 0.5 > horizon;
+        let p = (y as f32 + 0.5 - horizon).abs();
+        let height = if is_floor { pos_z } else { block - pos_z };
+        let row_distance = height * screen_height as f32 / p;
+        let row = &mut pixels[y * stride..y * stride + angles.len()];
+        let row_depth = &mut depth[y * stride..y * stride + angles.len()];
+
+        for (x, &angle) in angles.iter().enumerate() {
+            if row_depth[x] < f32::INFINITY {
+                continue;
+            }
+
+            let (dir_x, dir_y) = directions[x];
+            let world_x = player.pos.x + row_distance * dir_x;
+            let world_y = player.pos.y + row_distance * dir_y;
+            let (cell_x, cell_y) = (world_x / block, world_y / block);
+            let surface = maze.surface_at(cell_x.max(0.0) as usize, cell_y.max(0.0) as usize);
+
+            row[x] = if is_floor {
+                row_depth[x] = row_distance;
+                let light = lights.at_smooth(world_x, world_y);
+                let floor_color = texture_manager.get_floor_color(surface.floor, cell_x, cell_y);
+                if maze.cell_at(world_x, world_y, block_size) == Some('X') {
+                    // La salida brilla en verde para que se distinga en la oscuridad
+                    apply_distance_shading(multiply_color(floor_color, EXIT_TINT), row_distance, 0.5, light)
+                } else {
+                    apply_distance_shading(floor_color, row_distance, 0.0, light)
+                }
+            } else {
+                match texture_manager.get_ceiling_color(surface.ceiling, cell_x, cell_y) {
+                    Some(ceiling_color) => {
+                        row_depth[x] = row_distance;
+                        apply_distance_shading(ceiling_color, row_distance, 0.0, lights.at_smooth(world_x, world_y))
+                    }
+                    None => texture_manager.get_sky_color(angle, (y as f32 + sky_offset).max(0.0), screen_height as f32),
+                }
             }
         }
     }
-    
-    // Determinar tipo de conexión
-    let total_connections = vertical_connections + horizontal_connections;
-    let is_corner = total_connections <= 2;
-    let is_intersection = total_connections >= 3;
-    
-    WallContext {
-        has_vertical_walls,
-        has_horizontal_walls,
-        is_corner,
-        is_intersection,
-        vertical_connections,
-        horizontal_connections,
-    }
 }
 
-/// Obtener celda del mapa de manera segura
-fn get_maze_cell(maze: &Maze, x: i32, y: i32) -> Option<char> {
-    if x >= 0 && y >= 0 && y < maze.len() as i32 && x < maze[0].len() as i32 {
-        Some(maze[y as usize][x as usize])
-    } else {
-        None
+/// Elegir la textura según la cara golpeada: las esquinas '+' toman la
+/// textura de la pared que continúa en esa orientación, igual que las
+/// paredes de los fosos (que son la cara de una celda libre). Las
+/// diagonales usan la de las esquinas.
+fn wall_texture_char(intersection: &Intersect) -> char {
+    match intersection.impact {
+        '/' | '\\' => '+',
+        '+' | ' ' | 'X' if intersection.side.is_vertical() => '|',
+        '+' | ' ' | 'X' => '-',
+        other => other,
     }
 }
 
-/// Contar conexiones de pared para una celda
-fn count_wall_connections(maze: &Maze, x: i32, y: i32) -> usize {
-    let neighbors = [
-        (x - 1, y),     // Izquierda
-        (x + 1, y),     // Derecha
-        (x, y - 1),     // Arriba
-        (x, y + 1),     // Abajo
-    ];
-    
-    neighbors.iter()
-        .filter(|(nx, ny)| {
-            if let Some(cell) = get_maze_cell(maze, *nx, *ny) {
-                matches!(cell, '|' | '-' | '+')
-            } else {
-                false
-            }
-        })
-        .count()
+/// Las puertas con cerradura se tiñen del color de su llave
+fn door_lock_tint(maze: &Maze, intersection: &Intersect) -> Option<u32> {
+    if intersection.impact != 'D' {
+        return None;
+    }
+    let (col, row) = intersection.cell;
+    let lock = maze.door_at(col, row)?.lock?;
+    maze.key_def(lock).map(|k| k.color)
 }
 
-/// Aplicar sombreado basado en distancia
-fn apply_distance_shading(color: u32, distance: f32, flashlight_factor: f32) -> u32 {
+/// Aplicar sombreado basado en distancia, sumando por canal la luz de las
+/// luces puntuales (`light`)
+fn apply_distance_shading(color: u32, distance: f32, flashlight_factor: f32, light: [f32; 3]) -> u32 {
     let r = (color & 0xFF) as u8;
     let g = ((color >> 8) & 0xFF) as u8;
     let b = ((color >> 16) & 0xFF) as u8;
@@ -424,15 +511,29 @@ fn apply_distance_shading(color: u32, distance: f32, flashlight_factor: f32) ->
     let shade_factor = (1.0 - (distance / 500.0).min(0.7)).max(0.3) * global_darkness;
 
     // Efecto linterna (flashlight)
-    let final_factor = (shade_factor + flashlight_factor).min(1.0);
+    let final_factor = shade_factor + flashlight_factor;
 
-    let shaded_r = (r as f32 * final_factor) as u8;
-    let shaded_g = (g as f32 * final_factor) as u8;
-    let shaded_b = (b as f32 * final_factor) as u8;
+    let shaded_r = (r as f32 * (final_factor + light[0]).min(1.0)) as u8;
+    let shaded_g = (g as f32 * (final_factor + light[1]).min(1.0)) as u8;
+    let shaded_b = (b as f32 * (final_factor + light[2]).min(1.0)) as u8;
 
     rgba_to_u32(shaded_r, shaded_g, shaded_b, a)
 }
 
+/// Color de pared del render sin texturas, elegido por distancia para dar
+/// efecto de profundidad
+fn flat_wall_color(distance: f32) -> u32 {
+    if distance < 100.0 {
+        rgba_to_u32(200, 200, 200, 255) // Cerca
+    } else if distance < 200.0 {
+        rgba_to_u32(150, 150, 150, 255) // Medio
+    } else if distance < 400.0 {
+        rgba_to_u32(100, 100, 100, 255) // Lejos
+    } else {
+        rgba_to_u32(50, 50, 50, 255)    // Muy lejos
+    }
+}
+
 pub fn render_world(
     framebuffer: &mut Framebuffer,
     maze: &Maze,
@@ -443,52 +544,19 @@ pub fn render_world(
     let screen_height = framebuffer.height;
     let num_rays = screen_width;
     let fov = player.fov;
-    let half_screen_height = screen_height as f32 / 2.0;
-
-    // Pre-calcular colores para diferentes distancias/tipos de pared
-    let wall_colors = [
-        rgba_to_u32(200, 200, 200, 255), // Cerca
-        rgba_to_u32(150, 150, 150, 255), // Medio
-        rgba_to_u32(100, 100, 100, 255), // Lejos
-        rgba_to_u32(50, 50, 50, 255),    // Muy lejos
-    ];
+    let stop_height = maze.max_wall_height().max(player.eye_height());
+    let mut depth_buffer = vec![f32::INFINITY; (screen_width * screen_height) as usize];
+    let mut column = Column::new(screen_height as usize);
+    let mut steps = Vec::new();
 
     for i in 0..num_rays {
         let ray_ratio = i as f32 / num_rays as f32;
         let ray_angle = player.a - (fov / 2.0) + (ray_ratio * fov);
 
-        let intersection = cast_ray(
-            framebuffer,
-            maze,
-            player,
-            block_size,
-            ray_angle,
-            Vector2::new(0.0, 0.0),
-            false,
-        );
-
-        let corrected_distance = intersection.distance * (player.a - ray_angle).cos();
+        cast_ray_steps(maze, player.pos, ray_angle, block_size, stop_height, &mut steps);
 
-        let wall_height = if corrected_distance > 0.1 {
-            (block_size as f32 * screen_height as f32) / corrected_distance
-        } else {
-            screen_height as f32
-        };
-
-        let start_y = (half_screen_height - wall_height / 2.0).max(0.0) as u32;
-        let end_y = (half_screen_height + wall_height / 2.0).min(screen_height as f32) as u32;
-
-        // Seleccionar color basado en distancia para efecto de profundidad
-        let color_index = if corrected_distance < 100.0 { 0 }
-                         else if corrected_distance < 200.0 { 1 }
-                         else if corrected_distance < 400.0 { 2 }
-                         else { 3 };
-        
-        let wall_color = wall_colors[color_index];
-
-        // Usar draw_vertical_line optimizada para mejor rendimiento
-        if start_y < end_y {
-            framebuffer.draw_vertical_line(i as u32, start_y, end_y - 1, wall_color);
-        }
+        column.clear();
+        draw_ray_steps(&mut column, &steps, ray_angle, player, maze, block_size, None, 0.0);
+        column.copy_to(framebuffer.pixels_mut(), &mut depth_buffer, i as usize, screen_width as usize);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,323 @@
+use std::fs;
+
+use crate::capture::FrameCapture;
+use crate::events::PlayerInput;
+use crate::game::{parse_level, GameState, LevelStats};
+use crate::platform::headless::HeadlessPlatform;
+use crate::play::{load_level_textures, load_sounds, play_level};
+use crate::texture::TextureManager;
+
+/// Encabezado de los archivos de grabación (versión del formato al final)
+const MAGIC: &[u8; 6] = b"RCREC2";
+/// Bytes de una racha: repeticiones (u16) + entrada del paso
+const RUN_SIZE: usize = 2 + 15;
+/// Bits del último byte de cada racha: las acciones del paso
+const USE_DOOR_BIT: u8 = 1;
+const JUMP_BIT: u8 = 1 << 1;
+const CROUCH_BIT: u8 = 1 << 2;
+
+/// Entrada de cada paso de simulación de una partida, junto con el nivel
+/// jugado. Como la simulación corre a paso fijo, repetir la misma entrada
+/// sobre el mismo nivel reproduce la partida exacta.
+pub struct Recording {
+    pub level_source: String,
+    pub ticks: Vec<PlayerInput>,
+}
+
+impl Recording {
+    pub fn new(level_source: &str) -> Self {
+        Recording { level_source: level_source.to_string(), ticks: Vec::new() }
+    }
+
+    /// Formato binario: encabezado, largo del nivel (u32) y su texto, y
+    /// después rachas de pasos iguales. Los números van en little endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&(self.level_source.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(self.level_source.as_bytes());
+
+        let mut i = 0;
+        while i < self.ticks.len() {
+            let tick = self.ticks[i];
+            let mut count = 1;
+            while i + count < self.ticks.len() && count < u16::MAX as usize && same_input(&self.ticks[i + count], &tick) {
+                count += 1;
+            }
+            bytes.extend_from_slice(&(count as u16).to_le_bytes());
+            bytes.push(tick.forward as i8 as u8);
+            bytes.push(tick.strafe as i8 as u8);
+            bytes.extend_from_slice(&tick.turn.to_le_bytes());
+            bytes.extend_from_slice(&tick.mouse_turn.to_le_bytes());
+            bytes.extend_from_slice(&tick.mouse_pitch.to_le_bytes());
+            bytes.push(action_bits(&tick));
+            i += count;
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < MAGIC.len() + 4 || &bytes[..MAGIC.len()] != MAGIC {
+            return Err("no es un archivo de grabación".to_string());
+        }
+        let mut offset = MAGIC.len();
+        let source_len = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+        offset += 4;
+        let source = bytes
+            .get(offset..offset + source_len)
+            .ok_or("grabación incompleta: falta el nivel")?;
+        let level_source = String::from_utf8(source.to_vec())
+            .map_err(|_| "el nivel de la grabación no es UTF-8 válido".to_string())?;
+        offset += source_len;
+
+        let runs = &bytes[offset..];
+        if !runs.len().is_multiple_of(RUN_SIZE) {
+            return Err("grabación incompleta: el último paso está cortado".to_string());
+        }
+        let mut ticks = Vec::new();
+        for run in runs.chunks_exact(RUN_SIZE) {
+            let count = u16::from_le_bytes([run[0], run[1]]) as usize;
+            let tick = PlayerInput {
+                forward: run[2] as i8 as f32,
+                strafe: run[3] as i8 as f32,
+                turn: f32::from_le_bytes(run[4..8].try_into().unwrap()),
+                mouse_turn: f32::from_le_bytes(run[8..12].try_into().unwrap()),
+                mouse_pitch: f32::from_le_bytes(run[12..16].try_into().unwrap()),
+                use_door: run[16] & USE_DOOR_BIT != 0,
+                jump: run[16] & JUMP_BIT != 0,
+                crouch: run[16] & CROUCH_BIT != 0,
+            };
+            ticks.extend(std::iter::repeat_n(tick, count));
+        }
+        Ok(Recording { level_source, ticks })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_bytes()).map_err(|e| format!("No se pudo escribir '{}': {}", path, e))
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("No se pudo leer '{}': {}", path, e))?;
+        Recording::from_bytes(&bytes).map_err(|e| format!("'{}': {}", path, e))
+    }
+}
+
+fn action_bits(input: &PlayerInput) -> u8 {
+    let mut bits = 0;
+    if input.use_door {
+        bits |= USE_DOOR_BIT;
+    }
+    if input.jump {
+        bits |= JUMP_BIT;
+    }
+    if input.crouch {
+        bits |= CROUCH_BIT;
+    }
+    bits
+}
+
+/// Comparación exacta, incluidos los bits de los giros
+fn same_input(a: &PlayerInput, b: &PlayerInput) -> bool {
+    a.forward == b.forward
+        && a.strafe == b.strafe
+        && a.turn.to_bits() == b.turn.to_bits()
+        && a.mouse_turn.to_bits() == b.mouse_turn.to_bits()
+        && a.mouse_pitch.to_bits() == b.mouse_pitch.to_bits()
+        && action_bits(a) == action_bits(b)
+}
+
+/// Origen de la entrada de cada paso de simulación
+pub enum InputTape {
+    /// Teclado y mouse, sin grabar
+    Live,
+    /// Teclado y mouse, guardando cada paso
+    Recording(Recording),
+    /// Pasos leídos de una grabación; `next` es el siguiente a usar
+    Replaying { recording: Recording, next: usize },
+}
+
+impl InputTape {
+    /// Preparar la entrada del próximo paso. Al grabar se guarda `input`
+    /// tal como llegó; al reproducir se reemplaza por el paso grabado.
+    /// Devuelve false cuando la grabación ya no tiene más pasos.
+    pub fn next_step(&mut self, input: &mut PlayerInput) -> bool {
+        match self {
+            InputTape::Live => true,
+            InputTape::Recording(recording) => {
+                recording.ticks.push(*input);
+                true
+            }
+            InputTape::Replaying { recording, next } => match recording.ticks.get(*next) {
+                Some(tick) => {
+                    *input = *tick;
+                    *next += 1;
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+
+    /// Progreso de la reproducción: (paso actual, total)
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        match self {
+            InputTape::Replaying { recording, next } => Some((*next, recording.ticks.len())),
+            _ => None,
+        }
+    }
+
+    pub fn into_recording(self) -> Option<Recording> {
+        match self {
+            InputTape::Recording(recording) => Some(recording),
+            _ => None,
+        }
+    }
+}
+
+/// Reproducir una grabación sin ventana, lo más rápido posible. Devuelve el
+/// estado final de la partida y sus resultados.
+pub fn replay_headless(recording: Recording, block_size: usize) -> Result<(GameState, LevelStats), String> {
+    let level = parse_level(&recording.level_source, block_size)
+        .map_err(|e| format!("nivel de la grabación: {}", e))?;
+
+    let mut texture_manager = TextureManager::new();
+    load_level_textures(&mut texture_manager, &level);
+
+    // Frames largos: el reloj simula varios pasos por cada frame dibujado
+    let mut platform = HeadlessPlatform::new(930, 630);
+    platform.frame_time = 0.25;
+    load_sounds(&mut platform);
+
+    let mut stats = LevelStats::default();
+    let mut tape = InputTape::Replaying { recording, next: 0 };
+    let mut capture = FrameCapture::new(1);
+    let state = play_level(&mut platform, level, &texture_manager, block_size, &mut stats, &mut tape, &mut capture);
+    Ok((state, stats))
+}
+
+/// Subcomando `replay <archivo> [--headless]`. Sin ventana imprime el
+/// resultado y sale con 0 solo si la grabación completa el nivel.
+pub fn run(args: &[String], block_size: usize) -> i32 {
+    let headless = args.iter().any(|a| a == "--headless") || cfg!(not(feature = "raylib-backend"));
+    let Some(path) = args.iter().find(|a| !a.starts_with("--")) else {
+        println!("Uso: replay <archivo> [--headless]");
+        return 2;
+    };
+    let recording = match Recording::load(path) {
+        Ok(recording) => recording,
+        Err(e) => {
+            println!("{}", e);
+            return 1;
+        }
+    };
+
+    if !headless {
+        #[cfg(feature = "raylib-backend")]
+        crate::app::watch_replay(recording, block_size);
+        return 0;
+    }
+
+    let ticks = recording.ticks.len();
+    match replay_headless(recording, block_size) {
+        Ok((state, stats)) => {
+            let outcome = match state {
+                GameState::LevelComplete => "nivel completado",
+                GameState::GameOver => "el jugador murió",
+                _ => "la grabación terminó antes del final del nivel",
+            };
+            println!("{} pasos reproducidos: {}", ticks, outcome);
+            println!(
+                "Tiempo: {:.2} s | Objetos recogidos: {} | Puntos: {} | Daño recibido: {}",
+                stats.elapsed, stats.pickups, stats.score, stats.damage_taken
+            );
+            if state == GameState::LevelComplete { 0 } else { 1 }
+        }
+        Err(e) => {
+            println!("{}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::Key;
+
+    /// Pasillo de tres celdas con la salida al final
+    const CORRIDOR: &str = "@spawn 1 1 0\n+--+--+\n|    X|\n+--+--+\n";
+
+    fn walk(forward: f32, ticks: usize) -> Recording {
+        let mut recording = Recording::new(CORRIDOR);
+        let input = PlayerInput { forward, ..PlayerInput::default() };
+        recording.ticks = vec![input; ticks];
+        recording
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut recording = walk(1.0, 300);
+        recording.ticks[10].mouse_turn = 0.05;
+        recording.ticks[20].use_door = true;
+        recording.ticks[30].strafe = -1.0;
+        recording.ticks[40].turn = 1.5;
+        recording.ticks[50].mouse_pitch = -0.1;
+        recording.ticks[60].jump = true;
+        for tick in recording.ticks[70..90].iter_mut() {
+            tick.crouch = true;
+        }
+
+        let bytes = recording.to_bytes();
+        // Las rachas de pasos iguales ocupan una sola entrada
+        assert!(bytes.len() < MAGIC.len() + 4 + CORRIDOR.len() + 16 * RUN_SIZE);
+
+        let loaded = Recording::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.level_source, CORRIDOR);
+        assert_eq!(loaded.ticks.len(), recording.ticks.len());
+        for (a, b) in loaded.ticks.iter().zip(recording.ticks.iter()) {
+            assert!(same_input(a, b));
+        }
+        assert!(Recording::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Recording::from_bytes(b"otra cosa").is_err());
+    }
+
+    #[test]
+    fn replay_reaches_exit() {
+        let (state, stats) = replay_headless(walk(1.0, 120), 30).unwrap();
+        assert!(state == GameState::LevelComplete);
+        assert!(stats.elapsed > 0.0 && stats.elapsed < 2.0);
+    }
+
+    #[test]
+    fn replay_is_deterministic() {
+        let (first_state, first) = replay_headless(walk(1.0, 120), 30).unwrap();
+        let (second_state, second) = replay_headless(walk(1.0, 120), 30).unwrap();
+        assert!(first_state == second_state);
+        assert_eq!(first.elapsed.to_bits(), second.elapsed.to_bits());
+    }
+
+    #[test]
+    fn replay_ends_when_ticks_run_out() {
+        // Caminando hacia atrás el jugador choca con la pared y no llega
+        let (state, stats) = replay_headless(walk(-1.0, 60), 30).unwrap();
+        assert!(state == GameState::Menu);
+        assert!((stats.elapsed - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn headless_runs_can_be_recorded_and_replayed() {
+        let level = parse_level(CORRIDOR, 30).unwrap();
+        let mut platform = HeadlessPlatform::new(320, 200);
+        platform.hold(Key::W);
+        platform.max_frames = Some(300);
+        let mut stats = LevelStats::default();
+        let mut tape = InputTape::Recording(Recording::new(CORRIDOR));
+        let state = play_level(&mut platform, level, &TextureManager::new(), 30, &mut stats, &mut tape, &mut FrameCapture::new(1));
+        assert!(state == GameState::LevelComplete);
+
+        let recording = tape.into_recording().unwrap();
+        let (replayed_state, replayed) = replay_headless(recording, 30).unwrap();
+        assert!(replayed_state == GameState::LevelComplete);
+        assert_eq!(replayed.elapsed.to_bits(), stats.elapsed.to_bits());
+    }
+}
@@ -0,0 +1,131 @@
+use image::{Rgba, RgbaImage};
+
+use crate::framebuffer::Framebuffer;
+use crate::game::{load_maze_with_sprites, Level};
+use crate::lights::LightMap;
+use crate::math::{Color, Vector2};
+use crate::play::{load_level_textures, load_sprite_textures};
+use crate::player::Player;
+use crate::renderer::render_world_with_textures_downscale;
+use crate::sprites::render_sprites;
+use crate::texture::TextureManager;
+
+/// Tamaño por defecto de las imágenes, el mismo de la ventana del juego
+const DEFAULT_WIDTH: u32 = 930;
+const DEFAULT_HEIGHT: u32 = 630;
+
+/// Dibujar la vista 3D de un nivel desde `pos` mirando hacia `angle`
+/// (radianes), sin ventana. Solo paredes, suelo, techo y sprites: sin
+/// minimapa ni HUD.
+pub fn render_view(
+    level: &Level,
+    pos: Vector2,
+    angle: f32,
+    width: u32,
+    height: u32,
+    texture_manager: &TextureManager,
+    block_size: usize,
+) -> Framebuffer {
+    let mut player = Player::new(pos);
+    player.a = angle;
+
+    let mut framebuffer = Framebuffer::new(width, height, Color::BLACK);
+    let lights = LightMap::from_sprites(&level.maze, &level.sprites, block_size);
+    let depth_buffer = render_world_with_textures_downscale(&mut framebuffer, &level.maze, &player, block_size, texture_manager, &lights);
+    render_sprites(&mut framebuffer, &level.sprites, &player, texture_manager, &depth_buffer, block_size);
+    framebuffer
+}
+
+/// Convertir el framebuffer en una imagen RGBA opaca
+pub fn framebuffer_to_image(framebuffer: &Framebuffer) -> RgbaImage {
+    RgbaImage::from_fn(framebuffer.width, framebuffer.height, |x, y| {
+        let pixel = framebuffer.pixels()[(y * framebuffer.width + x) as usize];
+        Rgba([pixel as u8, (pixel >> 8) as u8, (pixel >> 16) as u8, 255])
+    })
+}
+
+pub fn save_png(framebuffer: &Framebuffer, path: &str) -> Result<(), String> {
+    framebuffer_to_image(framebuffer)
+        .save_with_format(path, image::ImageFormat::Png)
+        .map_err(|e| format!("No se pudo guardar '{}': {}", path, e))
+}
+
+/// Subcomando `render <nivel> <salida.png> [--pos col fila] [--angle grados] [--size AxB]`.
+/// Sin `--pos` ni `--angle` usa la aparición del nivel. Devuelve el código
+/// de salida.
+pub fn run(args: &[String], block_size: usize) -> i32 {
+    let mut paths: Vec<&str> = Vec::new();
+    let mut cell: Option<(f32, f32)> = None;
+    let mut degrees: Option<f32> = None;
+    let (mut width, mut height) = (DEFAULT_WIDTH, DEFAULT_HEIGHT);
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--pos" => {
+                let col = iter.next().and_then(|v| v.parse::<f32>().ok());
+                let row = iter.next().and_then(|v| v.parse::<f32>().ok());
+                let (Some(col), Some(row)) = (col, row) else {
+                    println!("Se esperaban una columna y una fila después de '--pos'");
+                    return 2;
+                };
+                cell = Some((col, row));
+            }
+            "--angle" => {
+                let Some(value) = iter.next().and_then(|v| v.parse::<f32>().ok()) else {
+                    println!("Se esperaban grados después de '--angle'");
+                    return 2;
+                };
+                degrees = Some(value);
+            }
+            "--size" => {
+                let size = iter.next().and_then(|v| {
+                    let (w, h) = v.split_once('x')?;
+                    Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?))
+                });
+                let Some((w, h)) = size.filter(|&(w, h)| w > 0 && h > 0) else {
+                    println!("Se esperaba un tamaño como 930x630 después de '--size'");
+                    return 2;
+                };
+                (width, height) = (w, h);
+            }
+            path => paths.push(path),
+        }
+    }
+
+    let [level_path, output] = paths[..] else {
+        println!("Uso: render <nivel> <salida.png> [--pos col fila] [--angle grados] [--size AxB]");
+        return 2;
+    };
+
+    let level = match load_maze_with_sprites(level_path, block_size) {
+        Ok(level) => level,
+        Err(e) => {
+            println!("{}: error: {}", level_path, e);
+            return 1;
+        }
+    };
+
+    let mut texture_manager = TextureManager::new();
+    load_sprite_textures(&mut texture_manager);
+    load_level_textures(&mut texture_manager, &level);
+
+    // Las posiciones se dan en celdas; se mira desde el centro de la celda
+    let pos = match cell {
+        Some((col, row)) => Vector2::new((col + 0.5) * block_size as f32, (row + 0.5) * block_size as f32),
+        None => level.spawn,
+    };
+    let angle = degrees.map_or(level.facing, f32::to_radians);
+
+    let framebuffer = render_view(&level, pos, angle, width, height, &texture_manager, block_size);
+    match save_png(&framebuffer, output) {
+        Ok(_) => {
+            println!("Imagen guardada en '{}'", output);
+            0
+        }
+        Err(e) => {
+            println!("{}", e);
+            1
+        }
+    }
+}
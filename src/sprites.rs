@@ -1,9 +1,121 @@
-use raylib::prelude::*;
-use crate::framebuffer::Framebuffer;
+use crate::math::Vector2;
+use crate::framebuffer::{Framebuffer, rgba_to_u32};
 use crate::player::Player;
 use crate::texture::TextureManager;
+use crate::lights::LightDef;
 use std::f32::consts::PI;
-use std::time::Instant;
+
+/// Definición de un tipo de sprite del mapa: todo su comportamiento
+/// (animación, daño y efecto al recogerlo) sale de esta tabla
+pub struct SpriteKind {
+    pub name: &'static str,               // nombre usado en la leyenda `@sprite`
+    pub symbol: char,                     // carácter por defecto en el nivel
+    pub frames: &'static [&'static str],  // texturas (más de una = animado)
+    pub frame_duration: f32,
+    pub scale: f32,
+    pub damage: i32,
+    pub pickup: Option<PickupDef>,
+    pub light: Option<LightDef>,          // luz puntual que emite
+}
+
+/// Efecto de un sprite que se recoge al tocarlo
+pub struct PickupDef {
+    pub heal: i32,
+    pub score: u32,
+    pub gives_key: bool,
+    pub sound: Option<&'static str>,  // nombre del efecto en el AudioManager
+    pub message: &'static str,
+}
+
+/// Carácter del tipo de sprite usado para las llaves
+pub const KEY_SYMBOL: char = 'O';
+
+pub const SPRITE_KINDS: &[SpriteKind] = &[
+    SpriteKind {
+        name: "key",
+        symbol: KEY_SYMBOL,
+        frames: &["key"],
+        frame_duration: 0.0,
+        scale: 8.0,
+        damage: 0,
+        pickup: Some(PickupDef { heal: 0, score: 100, gives_key: true, sound: Some("pickup"), message: "Llave obtenida" }),
+        light: None,
+    },
+    SpriteKind {
+        name: "spike",
+        symbol: 'A',
+        frames: &["spike"],
+        frame_duration: 0.0,
+        scale: 12.0,
+        damage: 10,
+        pickup: None,
+        light: None,
+    },
+    SpriteKind {
+        name: "fire",
+        symbol: 'F',
+        frames: &["fire1", "fire2", "fire3"],
+        frame_duration: 0.2,
+        scale: 12.0,
+        damage: 20,
+        pickup: None,
+        light: Some(LightDef { color: 0xFF3C8CFF, radius: 3.0, intensity: 0.9, flicker: 0.3 }),
+    },
+    SpriteKind {
+        name: "heal",
+        symbol: 'H',
+        frames: &["heal1", "heal2", "heal3", "heal4"],
+        frame_duration: 0.2,
+        scale: 12.0,
+        damage: 0,
+        pickup: Some(PickupDef { heal: 25, score: 10, gives_key: false, sound: Some("pickup"), message: "+25 de salud" }),
+        light: None,
+    },
+];
+
+pub fn sprite_kind(symbol: char) -> Option<&'static SpriteKind> {
+    SPRITE_KINDS.iter().find(|k| k.symbol == symbol)
+}
+
+pub fn sprite_kind_by_name(name: &str) -> Option<&'static SpriteKind> {
+    SPRITE_KINDS.iter().find(|k| k.name == name)
+}
+
+/// Efecto concreto de un sprite recogible ya colocado en el mundo
+#[derive(Clone, Debug)]
+pub struct Pickup {
+    pub heal: i32,
+    pub score: u32,
+    pub key: Option<char>,
+    pub sound: Option<String>,
+    pub message: String,
+}
+
+impl Pickup {
+    pub fn from_def(def: &PickupDef, symbol: char) -> Self {
+        Pickup {
+            heal: def.heal,
+            score: def.score,
+            key: if def.gives_key { Some(symbol) } else { None },
+            sound: def.sound.map(|s| s.to_string()),
+            message: def.message.to_string(),
+        }
+    }
+
+    /// Aplicar el efecto al jugador. Devuelve false si no tendría efecto
+    /// (p. ej. curación con la salud llena) y el objeto debe quedarse.
+    pub fn apply(&self, player: &mut Player) -> bool {
+        // Las curaciones se quedan en el mundo si la salud ya está llena
+        if self.heal > 0 && self.key.is_none() && player.health >= player.max_health {
+            return false;
+        }
+        player.health = (player.health + self.heal).min(player.max_health);
+        if let Some(key) = self.key {
+            player.keys.push(key);
+        }
+        true
+    }
+}
 
 /// Representa un sprite en el mundo
 #[derive(Clone)]
@@ -11,8 +123,11 @@ pub struct Sprite {
     pub pos: Vector2,
     pub texture_name: String,
     pub scale: f32,
-    pub damaging: bool,
+    pub damage: i32,                       // Daño por contacto (0 = inofensivo)
     pub animation: Option<AnimatedSprite>, // Opcional para sprites estáticos
+    pub pickup: Option<Pickup>,            // Efecto al recogerlo, si es recogible
+    pub tint: Option<u32>,                 // Color que multiplica la textura
+    pub light: Option<LightDef>,           // Luz puntual que emite, si tiene
 }
 
 #[derive(Clone)]
@@ -20,44 +135,62 @@ pub struct AnimatedSprite {
     pub frames: Vec<String>,          // nombres de las texturas para cada frame
     pub frame_duration: f32,          // duración de cada frame en segundos
     pub current_frame: usize,         // frame actual
-    pub last_update: Instant,         // último momento de actualización
+    pub timer: f32,                   // segundos de juego en el frame actual
 }
 
 impl Sprite {
-    pub fn new_static(pos: Vector2, texture_name: String, scale: f32, damaging: bool) -> Self {
+    pub fn new_static(pos: Vector2, texture_name: String, scale: f32, damage: i32) -> Self {
         Sprite {
             pos,
             texture_name,
             scale,
-            damaging,
+            damage,
             animation: None,
+            pickup: None,
+            tint: None,
+            light: None,
         }
     }
 
-    pub fn new_animated(pos: Vector2, frames: Vec<String>, frame_duration: f32, scale: f32, damaging: bool) -> Self {
+    pub fn new_animated(pos: Vector2, frames: Vec<String>, frame_duration: f32, scale: f32, damage: i32) -> Self {
         Sprite {
             pos,
             texture_name: frames[0].clone(), // Usar el primer frame como textura inicial
             scale,
-            damaging,
+            damage,
             animation: Some(AnimatedSprite {
                 frames,
                 frame_duration,
                 current_frame: 0,
-                last_update: Instant::now(),
+                timer: 0.0,
             }),
+            pickup: None,
+            tint: None,
+            light: None,
         }
     }
 
-    pub fn update(&mut self) {
+    /// Crear un sprite a partir de su definición en `SPRITE_KINDS`
+    pub fn from_kind(kind: &SpriteKind, pos: Vector2) -> Self {
+        let mut sprite = if kind.frames.len() > 1 {
+            let frames = kind.frames.iter().map(|f| f.to_string()).collect();
+            Sprite::new_animated(pos, frames, kind.frame_duration, kind.scale, kind.damage)
+        } else {
+            Sprite::new_static(pos, kind.frames[0].to_string(), kind.scale, kind.damage)
+        };
+        sprite.pickup = kind.pickup.as_ref().map(|def| Pickup::from_def(def, kind.symbol));
+        sprite.light = kind.light;
+        sprite
+    }
+
+    /// Avanzar la animación `dt` segundos de tiempo de juego
+    pub fn update(&mut self, dt: f32) {
         if let Some(anim) = &mut self.animation {
-            let now = Instant::now();
-            let elapsed = now.duration_since(anim.last_update).as_secs_f32();
-            
-            if elapsed >= anim.frame_duration {
+            anim.timer += dt;
+            if anim.timer >= anim.frame_duration {
+                anim.timer -= anim.frame_duration;
                 anim.current_frame = (anim.current_frame + 1) % anim.frames.len();
                 self.texture_name = anim.frames[anim.current_frame].clone();
-                anim.last_update = now;
             }
         }
     }
@@ -65,21 +198,22 @@ impl Sprite {
 
 pub fn render_sprites(
     framebuffer: &mut Framebuffer,
-    sprites: &mut Vec<Sprite>,
+    sprites: &[Sprite],
     player: &Player,
     texture_manager: &TextureManager,
-    depth_buffer: &Vec<f32>,
+    depth_buffer: &[f32],
+    block_size: usize,
 ) {
     let screen_width = framebuffer.width as f32;
     let screen_height = framebuffer.height as f32;
-    let half_screen_height = screen_height / 2.0;
-
-    for sprite in sprites.iter_mut() {
-        sprite.update();
-    }
+    let horizon = player.horizon(screen_height);
+    // El borde superior de los sprites está a media altura de pared, a la
+    // altura de los ojos de pie; al saltar o agacharse se mueven en pantalla
+    let block = block_size as f32;
+    let eye_offset = (player.eye_height() - Player::STAND_EYE_HEIGHT) * block;
 
     // 1. Ordenar sprites de más lejos a más cerca
-    let mut sorted_sprites = sprites.clone();
+    let mut sorted_sprites = sprites.to_vec();
     sorted_sprites.sort_by(|a, b| {
         let dist_a = (a.pos.x - player.pos.x).powi(2) + (a.pos.y - player.pos.y).powi(2);
         let dist_b = (b.pos.x - player.pos.x).powi(2) + (b.pos.y - player.pos.y).powi(2);
@@ -118,7 +252,7 @@ pub fn render_sprites(
 
         // Calcular posición en pantalla
         let sprite_screen_x = (screen_width / 2.0 * (1.0 + angle_diff / (player.fov / 2.0))) - sprite_width / 2.0;
-        let sprite_screen_y = half_screen_height;
+        let sprite_screen_y = horizon + eye_offset * screen_height / distance;
 
         // Obtener textura
         if let Some(texture) = texture_manager.get_sprite_texture(&sprite.texture_name) {
@@ -129,11 +263,6 @@ pub fn render_sprites(
                     continue;
                 }
 
-                // Verificar depth buffer
-                if distance >= depth_buffer[screen_x as usize] {
-                    continue;
-                }
-
                 let tex_x = x as f32 / sprite_width;
                 
                 for y in 0..sprite_height as i32 {
@@ -142,8 +271,16 @@ pub fn render_sprites(
                         continue;
                     }
 
+                    // Verificar depth buffer (por píxel: asoman sobre las paredes bajas)
+                    if distance >= depth_buffer[screen_y as usize * screen_width as usize + screen_x as usize] {
+                        continue;
+                    }
+
                     let tex_y = y as f32 / sprite_height;
-                    let color = sample_sprite_texture(texture, tex_x, tex_y);
+                    let mut color = sample_sprite_texture(texture, tex_x, tex_y);
+                    if let Some(tint) = sprite.tint {
+                        color = multiply_color(color, tint);
+                    }
                     
                     // Solo dibujar si el pixel no es transparente
                     if (color >> 24) > 10 {
@@ -155,6 +292,47 @@ pub fn render_sprites(
     }
 }
 
+/// Recoger los sprites recogibles que el jugador está tocando: se aplica su
+/// efecto y se quitan del mundo. Devuelve los efectos aplicados para que el
+/// juego reproduzca su sonido y muestre su mensaje.
+pub fn collect_pickups(sprites: &mut Vec<Sprite>, player: &mut Player, radius: f32) -> Vec<Pickup> {
+    let mut collected = Vec::new();
+    sprites.retain(|sprite| {
+        let dx = sprite.pos.x - player.pos.x;
+        let dy = sprite.pos.y - player.pos.y;
+        match &sprite.pickup {
+            Some(pickup) if dx * dx + dy * dy < radius * radius && pickup.apply(player) => {
+                collected.push(pickup.clone());
+                false
+            }
+            _ => true,
+        }
+    });
+    collected
+}
+
+/// Aplicar al jugador el daño del sprite peligroso más fuerte que está
+/// tocando. Devuelve el daño recibido, si lo hubo.
+pub fn apply_sprite_damage(sprites: &[Sprite], player: &mut Player, radius: f32) -> Option<i32> {
+    let damage = sprites
+        .iter()
+        .filter(|sprite| {
+            let dx = sprite.pos.x - player.pos.x;
+            let dy = sprite.pos.y - player.pos.y;
+            sprite.damage > 0 && dx * dx + dy * dy < radius * radius
+        })
+        .map(|sprite| sprite.damage)
+        .max()?;
+
+    if player.take_damage(damage) { Some(damage) } else { None }
+}
+
+/// Multiplicar dos colores RGBA canal por canal (conserva el alfa del primero)
+pub fn multiply_color(color: u32, tint: u32) -> u32 {
+    let channel = |shift: u32| (((color >> shift) & 0xFF) * ((tint >> shift) & 0xFF) / 255) as u8;
+    rgba_to_u32(channel(0), channel(8), channel(16), (color >> 24) as u8)
+}
+
 /// Función auxiliar para muestrear textura de sprite (consistente con TextureManager)
 fn sample_sprite_texture(texture: &crate::texture::TextureData, u: f32, v: f32) -> u32 {
     // Asegurar que las coordenadas estén en rango [0,1)
@@ -1,14 +1,14 @@
-use raylib::prelude::*;
-use crate::framebuffer::{Framebuffer, rgba_to_u32};
+use crate::framebuffer::rgba_to_u32;
 use std::collections::HashMap;
-use image;
 
 pub struct TextureManager {
     // Almacenar texturas como datos de píxeles para acceso rápido
     wall_textures: HashMap<char, TextureData>,
     sprite_textures: HashMap<String, TextureData>,
     floor_texture: Option<TextureData>,
-    ceiling_texture: Option<TextureData>,
+    ceiling_texture: Option<TextureData>,   // techo de todo el nivel; sin él se ve el cielo
+    sky_texture: Option<TextureData>,
+    surface_textures: Vec<Option<TextureData>>, // suelos/techos por celda, en el orden del nivel
 }
 
 #[derive(Clone)]
@@ -25,11 +25,13 @@ impl TextureManager {
             sprite_textures: HashMap::new(),
             floor_texture: None,
             ceiling_texture: None,
+            sky_texture: None,
+            surface_textures: Vec::new(),
         }
     }
 
     /// Cargar textura de pared para un carácter específico del maze
-    pub fn load_wall_texture(&mut self, wall_char: char, filename: &str, _rl: &mut RaylibHandle, _thread: &RaylibThread) -> Result<(), String> {
+    pub fn load_wall_texture(&mut self, wall_char: char, filename: &str) -> Result<(), String> {
         match self.load_texture_data(filename) {
             Ok(texture_data) => {
                 self.wall_textures.insert(wall_char, texture_data);
@@ -40,7 +42,7 @@ impl TextureManager {
     }
 
     /// Cargar textura de suelo
-    pub fn load_floor_texture(&mut self, filename: &str, _rl: &mut RaylibHandle, _thread: &RaylibThread) -> Result<(), String> {
+    pub fn load_floor_texture(&mut self, filename: &str) -> Result<(), String> {
         match self.load_texture_data(filename) {
             Ok(texture_data) => {
                 self.floor_texture = Some(texture_data);
@@ -50,8 +52,8 @@ impl TextureManager {
         }
     }
 
-    /// Cargar textura de techo/cielo
-    pub fn load_ceiling_texture(&mut self, filename: &str, _rl: &mut RaylibHandle, _thread: &RaylibThread) -> Result<(), String> {
+    /// Cargar textura de techo
+    pub fn load_ceiling_texture(&mut self, filename: &str) -> Result<(), String> {
         match self.load_texture_data(filename) {
             Ok(texture_data) => {
                 self.ceiling_texture = Some(texture_data);
@@ -61,12 +63,29 @@ impl TextureManager {
         }
     }
 
+    /// Cargar la siguiente textura de suelo/techo por celda. Si falla queda
+    /// un hueco para no desplazar los índices de las demás.
+    pub fn load_surface_texture(&mut self, filename: &str) -> Result<(), String> {
+        match self.load_texture_data(filename) {
+            Ok(texture_data) => {
+                self.surface_textures.push(Some(texture_data));
+                Ok(())
+            }
+            Err(e) => {
+                self.surface_textures.push(None);
+                Err(format!("Error cargando textura de suelo/techo '{}': {}", filename, e))
+            }
+        }
+    }
+
+    pub fn clear_surface_textures(&mut self) {
+        self.surface_textures.clear();
+    }
+
     pub fn load_sprite_texture(
         &mut self,
         sprite_name: &str,
         filename: &str,
-        _rl: &mut RaylibHandle,
-        _thread: &RaylibThread
     ) -> Result<(), String> {
         match self.load_texture_data(filename) {
             Ok(texture_data) => {
@@ -125,28 +144,44 @@ impl TextureManager {
                 '=' => rgba_to_u32(160, 82, 45, 255),   // Marrón claro (ladrillo)
                 '-' => rgba_to_u32(180, 82, 45, 255),   // Marrón claro (pared horizontal)
                 '|' => rgba_to_u32(160, 92, 55, 255),   // Marrón medio (pared vertical)
+                'D' => rgba_to_u32(90, 100, 110, 255),  // Gris azulado (puerta)
                 _ => rgba_to_u32(255, 0, 0, 255),       // Rojo por defecto
             }
         }
     }
 
-    /// Obtener color de suelo en coordenadas específicas
-    pub fn get_floor_color(&self, world_x: f32, world_y: f32) -> u32 {
-        if let Some(texture) = &self.floor_texture {
-            // Mapear coordenadas del mundo a coordenadas de textura
-            let texture_x = (world_x / 30.0) % 1.0; // Repetir cada 30 unidades (block_size)
-            let texture_y = (world_y / 30.0) % 1.0;
-            self.sample_texture(texture, texture_x, texture_y)
+    /// Color del suelo en (u, v) dentro de una celda (0.0-1.0). `surface`
+    /// es la textura propia de la celda, si tiene.
+    #[inline]
+    pub fn get_floor_color(&self, surface: Option<usize>, u: f32, v: f32) -> u32 {
+        if let Some(texture) = self.surface_texture(surface).or(self.floor_texture.as_ref()) {
+            self.sample_texture(texture, u, v)
         } else {
             rgba_to_u32(64, 64, 64, 255) // Gris oscuro por defecto
         }
     }
 
-    /// Obtener color de techo/cielo
-    pub fn get_ceiling_color(&self, screen_x: f32, screen_y: f32, screen_width: f32, screen_height: f32) -> u32 {
-        if let Some(texture) = &self.ceiling_texture {
-            // Para el cielo, podemos usar coordenadas de pantalla directamente
-            let texture_x = (screen_x / screen_width) % 1.0;
+    /// Color del techo en (u, v) dentro de una celda, o `None` si la celda
+    /// no tiene techo y se ve el cielo
+    #[inline]
+    pub fn get_ceiling_color(&self, surface: Option<usize>, u: f32, v: f32) -> Option<u32> {
+        self.surface_texture(surface)
+            .or(self.ceiling_texture.as_ref())
+            .map(|texture| self.sample_texture(texture, u, v))
+    }
+
+    /// La celda tiene techo (propio o el general del nivel); sin techo se ve el cielo
+    #[inline]
+    pub fn has_ceiling(&self, surface: Option<usize>) -> bool {
+        self.surface_texture(surface).is_some() || self.ceiling_texture.is_some()
+    }
+
+    /// Color del cielo para un rayo con ángulo `angle` (radianes). La
+    /// textura da una vuelta completa alrededor del jugador, así el cielo
+    /// gira con la cámara.
+    pub fn get_sky_color(&self, angle: f32, screen_y: f32, screen_height: f32) -> u32 {
+        if let Some(texture) = &self.sky_texture {
+            let texture_x = (angle / (2.0 * std::f32::consts::PI)).rem_euclid(1.0);
             let texture_y = (screen_y / screen_height) % 1.0;
             self.sample_texture(texture, texture_x, texture_y)
         } else {
@@ -159,6 +194,10 @@ impl TextureManager {
         }
     }
 
+    fn surface_texture(&self, surface: Option<usize>) -> Option<&TextureData> {
+        surface.and_then(|i| self.surface_textures.get(i)).and_then(Option::as_ref)
+    }
+
     /// Obtener datos de textura de sprite
     pub fn get_sprite_texture(&self, sprite_name: &str) -> Option<&TextureData> {
         self.sprite_textures.get(sprite_name)
@@ -196,11 +235,16 @@ impl TextureManager {
         let wood_v_texture = self.generate_wood_texture(64, 64, false);
         self.wall_textures.insert('|', wood_v_texture);
 
+        // Textura de puerta metálica
+        let door_texture = self.generate_door_texture(64, 64);
+        self.wall_textures.insert('D', door_texture);
+
         // Textura de suelo
         self.floor_texture = Some(self.generate_floor_texture(64, 64));
 
-        // Textura de cielo
-        self.ceiling_texture = Some(self.generate_sky_texture(256, 128));
+        // Cielo y sin techo: los niveles declaran su techo con @ceiling
+        self.sky_texture = Some(self.generate_sky_texture(256, 128));
+        self.ceiling_texture = None;
     }
 
     /// Generar textura procedural de ladrillo
@@ -212,11 +256,10 @@ impl TextureManager {
                 let brick_w = width / 8;
                 let brick_h = height / 4;
                 
-                let brick_x = x / brick_w;
                 let brick_y = y / brick_h;
                 
                 // Alternar ladrillos en filas
-                let offset = if brick_y % 2 == 0 { 0 } else { brick_w / 2 };
+                let offset = if brick_y.is_multiple_of(2) { 0 } else { brick_w / 2 };
                 let local_x = (x + offset) % brick_w;
                 let local_y = y % brick_h;
                 
@@ -275,9 +318,9 @@ impl TextureManager {
                 let base_brown = 139.0;
                 let variation = grain * 40.0 + cross_var * 20.0;
                 
-                let r = (base_brown + variation).max(100.0).min(180.0) as u8;
-                let g = ((base_brown + variation) * 0.5).max(50.0).min(120.0) as u8;
-                let b = ((base_brown + variation) * 0.2).max(10.0).min(60.0) as u8;
+                let r = (base_brown + variation).clamp(100.0, 180.0) as u8;
+                let g = ((base_brown + variation) * 0.5).clamp(50.0, 120.0) as u8;
+                let b = ((base_brown + variation) * 0.2).clamp(10.0, 60.0) as u8;
                 
                 pixels.push(rgba_to_u32(r, g, b, 255));
             }
@@ -286,6 +329,34 @@ impl TextureManager {
         TextureData { width, height, pixels }
     }
 
+    /// Generar textura procedural de puerta metálica con paneles y remaches
+    fn generate_door_texture(&self, width: u32, height: u32) -> TextureData {
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+
+        for y in 0..height {
+            for x in 0..width {
+                let border = x < 3 || x >= width - 3 || y < 3 || y >= height - 3;
+                // Dos paneles hundidos separados por una franja central
+                let panel_gap = y > height / 2 - 2 && y < height / 2 + 2;
+                let panel_edge = x == 8 || x == width - 9;
+                let rivet = (x == 5 || x == width - 6) && y % 8 == 4;
+
+                let (r, g, b) = if border || rivet {
+                    (60, 65, 70)
+                } else if panel_gap || panel_edge {
+                    (70, 80, 90)
+                } else {
+                    let noise = ((x * 7 + y * 13) % 11) as u8;
+                    (95 + noise, 105 + noise, 115 + noise)
+                };
+
+                pixels.push(rgba_to_u32(r, g, b, 255));
+            }
+        }
+
+        TextureData { width, height, pixels }
+    }
+
     /// Generar textura procedural de suelo
     fn generate_floor_texture(&self, width: u32, height: u32) -> TextureData {
         let mut pixels = Vec::with_capacity((width * height) as usize);
@@ -297,7 +368,7 @@ impl TextureManager {
                 let tile_x = x / tile_size;
                 let tile_y = y / tile_size;
                 
-                let is_dark_tile = (tile_x + tile_y) % 2 == 0;
+                let is_dark_tile = (tile_x + tile_y).is_multiple_of(2);
                 
                 // Añadir variación dentro de cada baldosa
                 let local_x = x % tile_size;
@@ -320,7 +391,7 @@ impl TextureManager {
         let mut pixels = Vec::with_capacity((width * height) as usize);
 
         for y in 0..height {
-            for x in 0..width {
+            for _ in 0..width {
                 let progress = y as f32 / height as f32;
 
                 // Zona superior: azul muy oscuro con toque morado
@@ -341,11 +412,7 @@ impl TextureManager {
                     (gray_tint, gray_tint, blue_intensity)
                 };
 
-                let final_r = r as u8;
-                let final_g = g as u8;
-                let final_b = b as u8;
-
-                pixels.push(rgba_to_u32(final_r, final_g, final_b, 255));
+                pixels.push(rgba_to_u32(r, g, b, 255));
             }
         }
 
@@ -0,0 +1,331 @@
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+
+use crate::door::DoorState;
+use crate::events::can_move_to;
+use crate::game::{parse_level, is_open_cell, Level, LevelError, Maze};
+
+/// Caracteres de pared que tienen textura
+const WALL_CHARS: [char; 6] = ['+', '-', '|', '/', '\\', '#'];
+
+/// Subcomando `validate-level`: revisar cada archivo e imprimir sus
+/// diagnósticos. Devuelve el código de salida del proceso.
+pub fn run(files: &[&str], block_size: usize) -> i32 {
+    let mut failed = 0;
+    for file in files {
+        let errors = validate_level(file, block_size);
+        if errors.is_empty() {
+            println!("{}: OK", file);
+            continue;
+        }
+        failed += 1;
+        for error in errors.iter() {
+            if error.line == 0 {
+                println!("{}: error: {}", file, error.message);
+            } else {
+                println!("{}:{}:{}: error: {}", file, error.line, error.column, error.message);
+            }
+        }
+    }
+
+    if failed > 0 {
+        println!("{} de {} niveles con errores", failed, files.len());
+        1
+    } else {
+        0
+    }
+}
+
+/// Revisar un nivel y devolver todos los problemas encontrados
+pub fn validate_level(filename: &str, block_size: usize) -> Vec<LevelError> {
+    let source = match fs::read_to_string(filename) {
+        Ok(source) => source,
+        Err(e) => return vec![LevelError::at(0, 0, format!("no se pudo leer el archivo: {}", e))],
+    };
+    validate_source(&source, block_size)
+}
+
+/// Revisar el texto de un nivel ya leído (o generado)
+pub fn validate_source(source: &str, block_size: usize) -> Vec<LevelError> {
+    let level = match parse_level(source, block_size) {
+        Ok(level) => level,
+        Err(e) => return vec![e],
+    };
+
+    let mut errors = Vec::new();
+    check_grid(&level, &mut errors);
+    check_reachability(&level, block_size, &mut errors);
+    check_assets(source, &mut errors);
+    errors
+}
+
+/// Filas rectangulares, paredes exteriores cerradas y caracteres conocidos
+fn check_grid(level: &Level, errors: &mut Vec<LevelError>) {
+    let maze = &level.maze;
+    let width = maze[0].len();
+    let last_row = maze.len() - 1;
+
+    for (row, cells) in maze.iter().enumerate() {
+        let line = level.grid_line + row;
+        if cells.len() != width {
+            errors.push(LevelError::at(line, cells.len().min(width) + 1,
+                format!("la fila tiene {} columnas, se esperaban {}", cells.len(), width)));
+        }
+
+        for (col, &ch) in cells.iter().enumerate() {
+            if !is_open_cell(ch) && ch != 'D' && !WALL_CHARS.contains(&ch) {
+                errors.push(LevelError::at(line, col + 1, format!("carácter desconocido '{}'", ch)));
+                continue;
+            }
+            let border = row == 0 || row == last_row || col == 0 || col + 1 == cells.len();
+            if border && (is_open_cell(ch) || ch == 'D') {
+                errors.push(LevelError::at(line, col + 1, "hueco en la pared exterior"));
+            }
+        }
+    }
+}
+
+/// La salida y todas las llaves deben poder alcanzarse desde el punto de
+/// aparición, recogiendo llaves por el camino para abrir cerraduras. Cada
+/// llave abre un solo grupo de puertas, como en el juego.
+fn check_reachability(level: &Level, block_size: usize, errors: &mut Vec<LevelError>) {
+    let maze = &level.maze;
+    let block = block_size as f32;
+    let start = ((level.spawn.x / block) as usize, (level.spawn.y / block) as usize);
+
+    let key_cells: Vec<(char, usize, usize)> = level
+        .sprites
+        .iter()
+        .filter_map(|s| {
+            let key = s.pickup.as_ref()?.key?;
+            Some((key, (s.pos.x / block) as usize, (s.pos.y / block) as usize))
+        })
+        .collect();
+
+    // Cada llave se gasta al abrir un grupo de puertas, así que el orden
+    // importa: se prueban los órdenes de apertura posibles. Un estado es el
+    // conjunto de grupos con cerradura ya abiertos.
+    let groups = locked_groups(maze);
+    let mut doors = maze.clone();
+    let mut reachable: Vec<Vec<bool>> = maze.iter().map(|row| vec![false; row.len()]).collect();
+    let mut seen: HashSet<Vec<bool>> = HashSet::new();
+    let mut pending = vec![vec![false; groups.len()]];
+    while let Some(opened) = pending.pop() {
+        if !seen.insert(opened.clone()) {
+            continue;
+        }
+
+        // Abiertas del todo las puertas sin cerradura y las de los grupos abiertos
+        for door in doors.doors.iter_mut() {
+            door.state = if door.lock.is_none() { DoorState::Open } else { DoorState::Closed };
+        }
+        for ((_, members), _) in groups.iter().zip(opened.iter()).filter(|(_, is_open)| **is_open) {
+            for &i in members.iter() {
+                doors.doors[i].state = DoorState::Open;
+            }
+        }
+        let cells = reachable_cells(&doors, start, block_size);
+        for (total, row) in reachable.iter_mut().zip(cells.iter()) {
+            for (total, &cell) in total.iter_mut().zip(row.iter()) {
+                *total |= cell;
+            }
+        }
+
+        for (index, (lock, members)) in groups.iter().enumerate() {
+            if opened[index] {
+                continue;
+            }
+            let found = key_cells
+                .iter()
+                .filter(|(key, col, row)| key == lock && cells[*row][*col])
+                .count();
+            let spent = groups
+                .iter()
+                .zip(opened.iter())
+                .filter(|((key, _), is_open)| **is_open && key == lock)
+                .count();
+            let at_door = members.iter().any(|&i| next_to_reachable(&cells, maze.doors[i].col, maze.doors[i].row));
+            if found > spent && at_door {
+                let mut next = opened.clone();
+                next[index] = true;
+                pending.push(next);
+            }
+        }
+    }
+
+    for &(key, col, row) in key_cells.iter() {
+        if !reachable[row][col] {
+            errors.push(LevelError::at(level.grid_line + row, col + 1,
+                format!("la llave '{}' no es alcanzable desde el punto de aparición", key)));
+        }
+    }
+
+    for door in maze.doors.iter() {
+        if let Some(lock) = door.lock
+            && !key_cells.iter().any(|(key, _, _)| *key == lock)
+        {
+            errors.push(LevelError::at(level.grid_line + door.row, door.col + 1,
+                format!("la puerta necesita la llave '{}' pero el nivel no la tiene", lock)));
+        }
+    }
+
+    let exits: Vec<(usize, usize)> = maze
+        .iter()
+        .enumerate()
+        .flat_map(|(row, cells)| {
+            cells.iter().enumerate().filter(|(_, ch)| **ch == 'X').map(move |(col, _)| (col, row))
+        })
+        .collect();
+    match exits.first() {
+        None => errors.push(LevelError::at(0, 0, "el nivel no tiene salida 'X'")),
+        Some(&(col, row)) => {
+            if !exits.iter().any(|&(c, r)| reachable[r][c]) {
+                errors.push(LevelError::at(level.grid_line + row, col + 1,
+                    "la salida no es alcanzable desde el punto de aparición"));
+            }
+        }
+    }
+}
+
+/// Puertas con cerradura agrupadas como las abre `Maze::use_door`: las
+/// hojas contiguas con la misma cerradura se abren juntas con una llave
+fn locked_groups(maze: &Maze) -> Vec<(char, Vec<usize>)> {
+    let mut groups: Vec<(char, Vec<usize>)> = Vec::new();
+    for door in maze.doors.iter() {
+        let Some(lock) = door.lock else {
+            continue;
+        };
+        let members: Vec<usize> = maze
+            .linked_doors(door.col, door.row)
+            .into_iter()
+            .filter(|&i| maze.doors[i].lock == Some(lock))
+            .collect();
+        if !groups.iter().any(|(_, group)| *group == members) {
+            groups.push((lock, members));
+        }
+    }
+    groups
+}
+
+/// Si alguna celda vecina de (col, row) es alcanzable
+fn next_to_reachable(reachable: &[Vec<bool>], col: usize, row: usize) -> bool {
+    let neighbors = [
+        (col.wrapping_sub(1), row),
+        (col + 1, row),
+        (col, row.wrapping_sub(1)),
+        (col, row + 1),
+    ];
+    neighbors
+        .iter()
+        .any(|&(c, r)| reachable.get(r).and_then(|cells| cells.get(c)).copied().unwrap_or(false))
+}
+
+/// Pasos de la búsqueda por lado de celda: bastante finos para que el
+/// jugador quepa en los pasillos de una celda y entre paredes delgadas
+const SUBCELLS: usize = 4;
+
+/// Búsqueda en anchura de las posiciones del jugador, en pasos de
+/// subcelda y con la misma colisión que el juego (`can_move_to`): las
+/// paredes delgadas y las hojas de puerta frenan solo con sus tramos y los
+/// huecos más angostos que el jugador no cuentan como paso. Las puertas se
+/// cruzan según su estado en `maze`. Devuelve las celdas donde el jugador
+/// puede tener el centro.
+fn reachable_cells(maze: &Maze, start: (usize, usize), block_size: usize) -> Vec<Vec<bool>> {
+    let width = maze.iter().map(|row| row.len()).max().unwrap_or(0) * SUBCELLS;
+    let height = maze.len() * SUBCELLS;
+    let step = block_size as f32 / SUBCELLS as f32;
+    let fits = |x: usize, y: usize| can_move_to((x as f32 + 0.5) * step, (y as f32 + 0.5) * step, maze, block_size);
+    let subcells = |col: usize, row: usize| {
+        (0..SUBCELLS * SUBCELLS).map(move |i| (col * SUBCELLS + i % SUBCELLS, row * SUBCELLS + i / SUBCELLS))
+    };
+
+    let mut visited = vec![vec![false; width]; height];
+    let mut queue = VecDeque::new();
+    for (x, y) in subcells(start.0, start.1) {
+        if fits(x, y) {
+            visited[y][x] = true;
+            queue.push_back((x, y));
+        }
+    }
+
+    while let Some((x, y)) = queue.pop_front() {
+        let neighbors = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (nx, ny) in neighbors {
+            if nx < width && ny < height && !visited[ny][nx] && fits(nx, ny) {
+                visited[ny][nx] = true;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+
+    maze.iter()
+        .enumerate()
+        .map(|(row, cells)| {
+            (0..cells.len()).map(|col| subcells(col, row).any(|(x, y)| visited[y][x])).collect()
+        })
+        .collect()
+}
+
+/// Los archivos de texturas y música referenciados deben existir
+fn check_assets(source: &str, errors: &mut Vec<LevelError>) {
+    for (index, line) in source.lines().enumerate() {
+        let Some(directive) = line.strip_prefix('@') else {
+            continue;
+        };
+        let (command, path) = directive.split_once(' ').unwrap_or((directive, ""));
+        let mut path = path.trim();
+        // En las zonas la textura va después de las cuatro coordenadas
+        if matches!(command, "floor-area" | "ceiling-area") {
+            path = path.splitn(5, ' ').nth(4).unwrap_or("").trim();
+        }
+        if matches!(command, "wall" | "floor" | "ceiling" | "floor-area" | "ceiling-area" | "music") && !Path::new(path).exists() {
+            errors.push(LevelError::at(index + 1, command.len() + 3,
+                format!("no existe el archivo '{}'", path)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(source: &str) -> Vec<String> {
+        validate_source(source, 30).into_iter().map(|e| e.message).collect()
+    }
+
+    #[test]
+    fn each_key_opens_a_single_locked_door() {
+        let header = "@spawn 1 1 0\n@key O L dorada 255,215,0\n+----------+\n";
+        let one_key = format!("{}| O L  L  X|\n+----------+\n", header);
+        assert_eq!(messages(&one_key), vec!["la salida no es alcanzable desde el punto de aparición"]);
+
+        let two_keys = format!("{}| O L OL  X|\n+----------+\n", header);
+        assert!(messages(&two_keys).is_empty());
+    }
+
+    #[test]
+    fn keys_can_be_spent_in_any_order() {
+        // La puerta de la izquierda no lleva a nada; hay que gastar la
+        // única llave en la de la derecha
+        let source = "@spawn 4 1 0\n@key O L dorada 255,215,0\n+----------+\n|  L O  L X|\n+----------+\n";
+        assert!(messages(source).is_empty());
+    }
+
+    #[test]
+    fn thin_walls_only_block_their_segment() {
+        // Entre las dos paredes '-' de la columna 3 queda un hueco de una
+        // celda de alto por donde pasa el jugador
+        let between = "@spawn 1 1 0\n#######\n#  -  #\n#  - X#\n#######\n";
+        assert!(messages(between).is_empty());
+
+        // Media celda a cada lado de la pared no alcanza
+        let half = "@spawn 1 1 0\n#######\n#  #  #\n#  - X#\n#######\n";
+        assert_eq!(messages(half), vec!["la salida no es alcanzable desde el punto de aparición"]);
+    }
+}